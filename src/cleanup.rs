@@ -0,0 +1,49 @@
+use jsonwebtoken::get_current_timestamp;
+use serde::{Deserialize, Serialize};
+use surrealdb::{engine::remote::ws::Client, sql::Thing, Surreal};
+
+use crate::prelude::*;
+
+/// The number of records removed by a [CleanupTask] run
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct CleanupStats {
+    /// The number of expired auth sessions that were deleted
+    pub deleted_auth_sessions: u64,
+    /// The number of expired Email OTP codes that were cleared
+    pub cleared_email_otp_codes: u64,
+}
+
+/// Removes expired auth data from the database
+/// 
+/// # Example
+/// ```ignore
+/// let stats = CleanupTask::run(&db).await?;
+/// ```
+pub struct CleanupTask;
+
+impl CleanupTask {
+    /// Deletes expired sessions and clears expired MFA codes
+    #[tracing::instrument(skip_all, err)]
+    pub async fn run(db: &Surreal<Client>) -> AuthResult<CleanupStats> {
+        let mut response = db
+            .query("
+                DELETE auth_session WHERE expires < $now RETURN BEFORE;
+                UPDATE verifies SET code = NONE WHERE code != NONE AND expires < $now RETURN BEFORE;
+            ")
+            .bind(("now", get_current_timestamp()))
+            .await?;
+
+        let stats = CleanupStats {
+            deleted_auth_sessions: response.take::<Vec<Thing>>((0, "id"))?.len() as u64,
+            cleared_email_otp_codes: response.take::<Vec<Thing>>((1, "id"))?.len() as u64,
+        };
+
+        tracing::info!(
+            deleted_auth_sessions = stats.deleted_auth_sessions,
+            cleared_email_otp_codes = stats.cleared_email_otp_codes,
+            "Cleaned up expired auth data"
+        );
+
+        Ok(stats)
+    }
+}
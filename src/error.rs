@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use thiserror::Error;
-use crate::session::AuthSessionId;
+use surrealdb::sql::Thing;
+use crate::{session::AuthSessionId, user::credential::AuthMethodType};
  
 /// The error type
 #[non_exhaustive]
@@ -10,27 +12,44 @@ pub enum AuthError {
     #[error("The account was disabled!")]
     UserDisabled(Vec<String>),
     #[error("Failed to calculate hash!")]
-    HashFailed,
+    HashFailed(#[source] argon2::password_hash::Error),
     #[error("{0}")]
     CredentialDuplicate(String),
-    #[error("{0}")]
-    CredentialOnly(String),
+    /// The credential is the [User](crate::user::User)'s only authentication method, so it can't be removed
+    /// 
+    /// The credential is only included for logging, it isn't part of the message.
+    #[error("Cannot remove the only authentication method!")]
+    CredentialOnly {
+        credential: Thing,
+        credential_type: AuthMethodType,
+    },
     #[error("{0}")]
     CredentialNotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    /// Per-field validation messages (e.g. `password` -> "The password is too short!")
+    #[error("The input is invalid!")]
+    ValidationErrors(HashMap<String, String>),
     #[error("No MFA code was provided or it was incorrect!")]
     MfaRequired(AuthSessionId),
+    #[error("The MFA code is incorrect!")]
+    MfaIncorrect,
+    /// Too many incorrect MFA codes were tried with a pending session, so it was revoked
+    #[error("Too many incorrect MFA codes, please sign in again!")]
+    MfaAttemptsExceeded,
     #[error("IO error!")]
-    Io(std::io::Error),
+    Io(#[source] std::io::Error),
     #[error("{0}")]
     SaveFailed(String),
     #[error("{0}")]
     UpdateFailed(String), 
     #[error("The token is expired!")]
     TokenExpired,
+    /// The source is `None` when the token was decoded fine but failed another check
     #[error("The token is invalid!")]
-    TokenInvalid,
+    TokenInvalid(#[source] Option<jsonwebtoken::errors::Error>),
     #[error("Something went with the database!")]
-    DatabaseFailed(surrealdb::Error),
+    DatabaseFailed(#[source] surrealdb::Error),
     #[error("{0}")]
     Unknown(String),
 }
@@ -42,8 +61,8 @@ impl From<surrealdb::Error> for AuthError {
 }
 
 impl From<argon2::password_hash::Error> for AuthError {
-    fn from(_value: argon2::password_hash::Error) -> Self {
-        Self::HashFailed
+    fn from(value: argon2::password_hash::Error) -> Self {
+        Self::HashFailed(value)
     }
 }
 
@@ -54,8 +73,8 @@ impl From<std::io::Error> for AuthError {
 }
 
 impl From<jsonwebtoken::errors::Error> for AuthError {
-    fn from(_value: jsonwebtoken::errors::Error) -> Self {
-        Self::TokenInvalid
+    fn from(value: jsonwebtoken::errors::Error) -> Self {
+        Self::TokenInvalid(Some(value))
     }
 }
 
@@ -67,3 +86,50 @@ impl From<totp_rs::TotpUrlError> for AuthError {
 
 /// A time-saving replacement for [Result]
 pub type AuthResult<T> = std::result::Result<T, AuthError>;
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use surrealdb::sql::Thing;
+    use crate::user::credential::AuthMethodType;
+    use super::AuthError;
+
+    #[test]
+    fn error_io_source() {
+        let error = AuthError::from(std::io::Error::other("disk on fire"));
+
+        assert_eq!(error.source().unwrap().to_string(), "disk on fire")
+    }
+
+    #[test]
+    fn error_hash_source() {
+        let error = AuthError::from(argon2::password_hash::Error::Password);
+
+        assert!(error.source().is_some())
+    }
+
+    #[test]
+    fn error_token_source() {
+        let error = AuthError::from(jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken));
+
+        assert!(error.source().is_some())
+    }
+
+    #[test]
+    fn error_boxed() {
+        let error: Box<dyn Error> = AuthError::TokenInvalid(None).into();
+
+        assert_eq!(error.to_string(), "The token is invalid!")
+    }
+
+    #[test]
+    fn error_credential_only_hides_context() {
+        let error = AuthError::CredentialOnly {
+            credential: Thing::from(("credential", "secret@example.com")),
+            credential_type: AuthMethodType::EmailPassword,
+        };
+
+        assert_eq!(error.to_string(), "Cannot remove the only authentication method!");
+        assert!(format!("{error:?}").contains("EmailPassword"))
+    }
+}
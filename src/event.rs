@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{prelude::*, user::credential::AuthMethodType};
+
+/// The events that can be emitted to an [EventEmitter]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AuthEvent {
+    /// A [User] registered
+    UserCreated { user: Uuid, created: u64 },
+    /// A [User] was deleted
+    UserDeleted { user: Uuid },
+    /// A [User] authenticated with the given method
+    UserAuthenticated { user: Uuid, method: AuthMethodType },
+    /// A [User] changed their password
+    PasswordChanged { user: Uuid },
+}
+
+/// Receives the [AuthEvents](AuthEvent) emitted by the crate
+/// 
+/// This can be implemented to forward events to webhooks, queues, etc.
+/// 
+/// # Example
+/// ```ignore
+/// struct LogEmitter;
+/// 
+/// impl EventEmitter for LogEmitter {
+///     fn emit(&self, event: &AuthEvent) -> AuthResult<()> {
+///         println!("{event:?}");
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait EventEmitter: Send + Sync {
+    /// Handles the event
+    fn emit(&self, event: &AuthEvent) -> AuthResult<()>;
+}
+
+/// An [EventEmitter] that discards every event
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopEventEmitter;
+
+impl EventEmitter for NoopEventEmitter {
+    fn emit(&self, _event: &AuthEvent) -> AuthResult<()> {
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 //! Just an auth crate
 
+// `AuthError` wraps `surrealdb::Error`, which is large by design
+#![allow(clippy::result_large_err)]
+
 /// Traits for the builder pattern
 /// 
 /// The builder pattern allows the API's consumer to create
@@ -99,4 +102,30 @@ pub mod prelude;
 pub mod user;
 
 /// [User](crate::user::User) session management
-pub mod session;
\ No newline at end of file
+pub mod session;
+
+/// Auth events
+/// 
+/// Some operations (e.g. deleting a [User](crate::user::User)) emit an [AuthEvent](crate::event::AuthEvent)
+/// to an injected [EventEmitter](crate::event::EventEmitter), which lets the consuming
+/// application react to them without polling the database.
+pub mod event;
+
+/// Outgoing emails
+/// 
+/// Flows that need to reach the [User](crate::user::User) by email (e.g. Email OTP codes) take an injected
+/// [Mailer](crate::mailer::Mailer), which the consuming application implements with its own transport.
+pub mod mailer;
+
+/// Periodic cleanup of expired auth data
+/// 
+/// Expired sessions and MFA codes are never removed when they're read, so they
+/// accumulate over time. The consuming application should run a
+/// [CleanupTask](crate::cleanup::CleanupTask) on a schedule (e.g. every hour).
+pub mod cleanup;
+
+/// MFA enforcement policies
+/// 
+/// An [MfaPolicy](crate::mfa_policy::MfaPolicy) forces [Users](crate::user::User) to enroll
+/// an MFA method once their account reaches a certain age.
+pub mod mfa_policy;
\ No newline at end of file
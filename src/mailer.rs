@@ -0,0 +1,56 @@
+use crate::prelude::*;
+
+/// Sends the emails required by the auth flows
+/// 
+/// The crate doesn't ship an email transport: implement this trait on top of
+/// whatever the consuming application already uses (SMTP, an email API, etc.).
+/// 
+/// # Example
+/// ```ignore
+/// struct LogMailer;
+/// 
+/// impl Mailer for LogMailer {
+///     fn send_verification(&self, email: &str, token: &str) -> AuthResult<()> {
+///         println!("Verify {email} with {token}");
+///         Ok(())
+///     }
+/// 
+///     // ...
+/// }
+/// ```
+pub trait Mailer: Send + Sync {
+    /// Sends the email verification token
+    fn send_verification(&self, email: &str, token: &str) -> AuthResult<()>;
+    /// Sends the password reset token
+    fn send_password_reset(&self, email: &str, token: &str) -> AuthResult<()>;
+    /// Sends an invite token
+    fn send_invite(&self, email: &str, token: &str) -> AuthResult<()>;
+    /// Sends an Email OTP code
+    fn send_mfa_code(&self, email: &str, code: &str) -> AuthResult<()>;
+}
+
+/// A [Mailer] that only logs which emails would have been sent (the tokens aren't logged)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMailer;
+
+impl Mailer for NoopMailer {
+    fn send_verification(&self, email: &str, _token: &str) -> AuthResult<()> {
+        tracing::debug!(email, "Skipped sending the verification email");
+        Ok(())
+    }
+
+    fn send_password_reset(&self, email: &str, _token: &str) -> AuthResult<()> {
+        tracing::debug!(email, "Skipped sending the password reset email");
+        Ok(())
+    }
+
+    fn send_invite(&self, email: &str, _token: &str) -> AuthResult<()> {
+        tracing::debug!(email, "Skipped sending the invite email");
+        Ok(())
+    }
+
+    fn send_mfa_code(&self, email: &str, _code: &str) -> AuthResult<()> {
+        tracing::debug!(email, "Skipped sending the MFA code email");
+        Ok(())
+    }
+}
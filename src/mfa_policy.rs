@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Requires MFA enrollment once an account reaches a certain age
+/// 
+/// # Example
+/// ```ignore
+/// let policy = MfaPolicy {
+///     require_after_days: 30,
+///     grace_period_days: 7,
+/// };
+/// 
+/// if user.mfa_required(&db, &policy).await? {
+///     // Ask the user to enroll an MFA method
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MfaPolicy {
+    /// The account age (in days) after which MFA is required
+    pub require_after_days: u32,
+    /// The number of days the user has to enroll once MFA is required
+    pub grace_period_days: u32,
+}
+
+impl MfaPolicy {
+    const SECONDS_PER_DAY: u64 = 3600 * 24;
+
+    /// Checks whether the policy applies to an account of the given age
+    /// (i.e. the account is past the grace period)
+    pub fn is_enforced(&self, account_age: Duration) -> bool {
+        let deadline = (self.require_after_days as u64 + self.grace_period_days as u64) * Self::SECONDS_PER_DAY;
+
+        account_age >= Duration::from_secs(deadline)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::MfaPolicy;
+
+    const POLICY: MfaPolicy = MfaPolicy {
+        require_after_days: 30,
+        grace_period_days: 7,
+    };
+
+    #[test]
+    fn mfa_policy_within_grace_period() {
+        assert!(!POLICY.is_enforced(Duration::from_secs(36 * 3600 * 24)))
+    }
+
+    #[test]
+    fn mfa_policy_past_grace_period() {
+        assert!(POLICY.is_enforced(Duration::from_secs(37 * 3600 * 24)))
+    }
+}
@@ -1,6 +1,7 @@
+use jsonwebtoken::get_current_timestamp;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
-use surrealdb::{engine::remote::ws::Client, Surreal};
+use surrealdb::{engine::remote::ws::Client, sql::Thing, Surreal};
 use uuid::Uuid;
 
 use crate::prelude::{AuthError, AuthResult};
@@ -20,42 +21,174 @@ pub struct AuthSession {
     pub state: AuthSessionState,
     pub expires: u64,
     pub agent: Option<String>,
+    /// The number of MFA codes tried with this session while it was pending MFA
+    #[serde(default)]
+    pub attempts: u32,
 }
 
 impl AuthSession {
+    const PENDING_MFA_DURATION: u64 = 300;
+    const AUTHENTICATED_DURATION: u64 = 3600 * 24 * 7;
+    const MAX_MFA_ATTEMPTS: u32 = 5;
+
     pub fn new(user_id: &Uuid, state: AuthSessionState, agent: Option<String>) -> Self {
+        let duration = match state {
+            AuthSessionState::PendingMfa => Self::PENDING_MFA_DURATION,
+            AuthSessionState::Authenticated => Self::AUTHENTICATED_DURATION,
+        };
+
         Self {
-            id: String::from("sad"),
+            id: Uuid::new_v4().to_string(),
             user: user_id.to_string(),
             state,
-            expires: 1,
+            expires: get_current_timestamp() + duration,
             agent,
+            attempts: 0,
         }
     }
 
     pub async fn save(&self, db: &Surreal<Client>) -> AuthResult<Self> {
-        Ok(
-            db
+        db
             .query("CREATE auth_session CONTENT $session;")
             .bind(("session", self))
             .await?
             .take::<Option<Self>>(0)
             .map_err(|_| AuthError::CredentialDuplicate("This user is already authenticated!".into()))?
-            .ok_or(AuthError::SaveFailed("Failed to create the session!".into()))?
-            .into()
-        )
+            .ok_or(AuthError::SaveFailed("Failed to create the session!".into()))
     }
 
     pub async fn get_by_id(db: &Surreal<Client>, id: AuthSessionId) -> AuthResult<Self> {
-        Ok(
-            db 
+        db 
             .query("SELECT * FROM type::thing('auth_session', $session_id);")
             .bind(("session_id", id))
             .await?
             .take::<Option<Self>>(0)
             .map_err(|_| AuthError::CredentialDuplicate("This user is already authenticated!".into()))?
-            .ok_or(AuthError::SaveFailed("The session doesn't exist or couldn't be found!".into()))?
-            .into()
-        )
+            .ok_or(AuthError::SaveFailed("The session doesn't exist or couldn't be found!".into()))
+    }
+
+    /// Checks whether the session is waiting for an MFA code, hasn't expired and has attempts left
+    pub fn is_pending_mfa(&self) -> bool {
+        matches!(self.state, AuthSessionState::PendingMfa)
+            && get_current_timestamp() <= self.expires
+            && self.attempts < Self::MAX_MFA_ATTEMPTS
+    }
+
+    /// Counts an MFA attempt on the pending session, before its code is checked
+    /// 
+    /// The attempt is only counted if the session is still pending MFA and has attempts left,
+    /// and it's done in a single query so concurrent requests can't go over the limit.
+    /// Returns the number of attempts left after this one, or `None` if no attempt could be counted.
+    pub async fn count_mfa_attempt(db: &Surreal<Client>, id: AuthSessionId) -> AuthResult<Option<u32>> {
+        let session = db
+            .query("
+                UPDATE type::thing('auth_session', $session_id) SET attempts += 1 
+                WHERE state = $state AND expires >= $now AND (attempts = NONE OR attempts < $max_attempts)
+                RETURN AFTER;
+            ")
+            .bind(("session_id", id))
+            .bind(("state", AuthSessionState::PendingMfa.to_string()))
+            .bind(("now", get_current_timestamp()))
+            .bind(("max_attempts", Self::MAX_MFA_ATTEMPTS))
+            .await?
+            .take::<Option<Self>>(0)?;
+
+        Ok(session.map(|session| Self::MAX_MFA_ATTEMPTS.saturating_sub(session.attempts)))
+    }
+
+    /// Checks whether the session is fully authenticated and hasn't expired
+    pub fn is_valid(&self) -> bool {
+        matches!(self.state, AuthSessionState::Authenticated) && get_current_timestamp() <= self.expires
+    }
+
+    /// Checks whether the session still exists (it hasn't been revoked) and is valid
+    pub async fn is_active(db: &Surreal<Client>, id: AuthSessionId) -> AuthResult<bool> {
+        let session = db
+            .query("SELECT * FROM type::thing('auth_session', $session_id);")
+            .bind(("session_id", id))
+            .await?
+            .take::<Option<Self>>(0)?;
+
+        Ok(session.is_some_and(|session| session.is_valid()))
+    }
+
+    /// Deletes the session (if it exists)
+    pub async fn delete(db: &Surreal<Client>, id: AuthSessionId) -> AuthResult<()> {
+        db
+            .query("DELETE type::thing('auth_session', $session_id);")
+            .bind(("session_id", id))
+            .await?
+            .check()?;
+
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Deletes every session of the user (signs them out from all devices),
+    /// returning the number of deleted sessions
+    pub async fn revoke_all_for_user(db: &Surreal<Client>, user_id: &Uuid) -> AuthResult<u64> {
+        let revoked = db
+            .query("DELETE auth_session WHERE user = $user_id RETURN BEFORE;")
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take::<Vec<Thing>>((0, "id"))?
+            .len() as u64;
+
+        tracing::info!(user = %user_id, revoked, "Revoked all auth sessions");
+
+        Ok(revoked)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jsonwebtoken::get_current_timestamp;
+    use uuid::Uuid;
+    use super::{AuthSession, AuthSessionState};
+
+    #[test]
+    fn session_is_valid() {
+        let session = AuthSession::new(&Uuid::new_v4(), AuthSessionState::Authenticated, None);
+
+        assert!(session.is_valid())
+    }
+
+    #[test]
+    fn session_is_not_valid() {
+        let pending = AuthSession::new(&Uuid::new_v4(), AuthSessionState::PendingMfa, None);
+        let mut expired = AuthSession::new(&Uuid::new_v4(), AuthSessionState::Authenticated, None);
+        expired.expires = get_current_timestamp() - 1;
+
+        assert!(!pending.is_valid());
+        assert!(!expired.is_valid())
+    }
+
+    #[test]
+    fn session_is_pending_mfa() {
+        let pending = AuthSession::new(&Uuid::new_v4(), AuthSessionState::PendingMfa, None);
+        let authenticated = AuthSession::new(&Uuid::new_v4(), AuthSessionState::Authenticated, None);
+
+        assert!(pending.is_pending_mfa());
+        assert!(!authenticated.is_pending_mfa())
+    }
+
+    #[test]
+    fn session_mfa_attempts_exhausted() {
+        let mut pending = AuthSession::new(&Uuid::new_v4(), AuthSessionState::PendingMfa, None);
+        pending.attempts = AuthSession::MAX_MFA_ATTEMPTS - 1;
+
+        assert!(pending.is_pending_mfa());
+
+        pending.attempts = AuthSession::MAX_MFA_ATTEMPTS;
+
+        assert!(!pending.is_pending_mfa())
+    }
+
+    #[test]
+    fn session_deserialize_without_attempts() {
+        let session: AuthSession = serde_json::from_str(
+            r#"{ "id": "session", "user": "user", "state": "PendingMfa", "expires": 0, "agent": null }"#
+        ).unwrap();
+
+        assert_eq!(session.attempts, 0)
+    }
+}
@@ -30,18 +30,32 @@ pub mod attributes;
 /// Jwts and all sorts of auth tokens
 pub mod token;
 
-use std::str::FromStr;
+/// Validated email addresses
+/// 
+/// Emails are used as credential identifiers, so they're wrapped in an [Email](email::Email)
+/// which validates and normalizes them before they can reach the database.
+pub mod email;
+
+/// Plaintext passwords
+/// 
+/// Passwords are wrapped in a [Password](password::Password) which zeroes them on drop
+/// and keeps them out of logs and debug output.
+pub mod password;
+
+use std::{collections::HashMap, net::IpAddr, str::FromStr, time::Duration};
 
 use jsonwebtoken::get_current_timestamp;
-use serde::{Serialize, Deserialize};
+use serde::{ser::SerializeStruct, Serialize, Serializer, Deserialize};
+use strum::IntoEnumIterator;
 use surrealdb::{engine::remote::ws::Client, sql::{Value, Thing}, Surreal};
 use uuid::Uuid;
 
-use crate::{builder::*, session::{AuthSession, AuthSessionId, AuthSessionState}};
+use crate::{builder::*, event::{AuthEvent, EventEmitter}, mfa_policy::MfaPolicy, session::{AuthSession, AuthSessionId, AuthSessionState}};
 use crate::prelude::*;
 use metadata::UserMetadata;
-use self::{attributes::UserAttributes, credential::{AuthMethod, AuthMethodType, DbAuthMethod, MfaMethod, MfaMethodType}};
+use self::{attributes::UserAttributes, credential::{audit::{CredentialAudit, CredentialAuditAction}, AuthMethod, MfaCode, AuthMethodType, DbAuthMethod, MfaMethod, MfaMethodType}};
 use self::token::{IdToken, Token, TokenClaims, TokenType};
+use self::{credential::email_password::DbEmailPasswordMethod, email::Email};
 
 /// This struct contains the metadata and attributes of each user.
 /// 
@@ -61,6 +75,10 @@ use self::token::{IdToken, Token, TokenClaims, TokenType};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct User {
     /// This contains the [User]'s UUID.
+    /// 
+    /// It's always serialized in the hyphenated format, use [with_id_format](User::with_id_format)
+    /// if a different representation is needed.
+    #[serde(serialize_with = "serialize_hyphenated_id")]
     pub id: Uuid,
     /// The attributes are a set of custom fields that contain unique data
     /// about the user.
@@ -70,6 +88,126 @@ pub struct User {
     pub metadata: UserMetadata,
 }
 
+/// The string representation used when serializing a [User]'s UUID
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UserIdFormat {
+    /// e.g. `550e8400-e29b-41d4-a716-446655440000`
+    #[default]
+    Hyphenated,
+    /// e.g. `urn:uuid:550e8400-e29b-41d4-a716-446655440000`
+    Urn,
+    /// e.g. `550e8400e29b41d4a716446655440000`
+    Simple,
+}
+
+impl UserIdFormat {
+    /// Formats the UUID with this representation
+    pub fn format(&self, id: &Uuid) -> String {
+        match self {
+            Self::Hyphenated => id.hyphenated().to_string(),
+            Self::Urn => id.urn().to_string(),
+            Self::Simple => id.simple().to_string(),
+        }
+    }
+}
+
+fn serialize_hyphenated_id<S: Serializer>(id: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&UserIdFormat::Hyphenated.format(id))
+}
+
+/// A borrowed [User] which serializes its UUID with the given [UserIdFormat]
+/// 
+/// # Example
+/// ```ignore
+/// let json = serde_json::to_string(&user.with_id_format(UserIdFormat::Urn))?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct UserWithIdFormat<'a> {
+    user: &'a User,
+    format: UserIdFormat,
+}
+
+impl Serialize for UserWithIdFormat<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut user = serializer.serialize_struct("User", 3)?;
+        user.serialize_field("id", &self.format.format(&self.user.id))?;
+        user.serialize_field("attributes", &self.user.attributes)?;
+        user.serialize_field("metadata", &self.user.metadata)?;
+        user.end()
+    }
+}
+
+/// Filters used when listing [Users](User)
+/// 
+/// Every field set to `None` is ignored.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct UserFilter {
+    /// Whether the [User] has an email/password credential
+    pub has_email_password: Option<bool>,
+    /// Whether the [User]'s account is disabled
+    pub disabled: Option<bool>,
+    /// Only includes [Users](User) created after this timestamp
+    /// 
+    /// [Users](User) with an unknown creation time (registered before it was tracked)
+    /// are excluded unless [include_unknown_created](Self::include_unknown_created) is set
+    pub created_after: Option<u64>,
+    /// Whether [created_after](Self::created_after) also includes [Users](User) with an unknown creation time
+    #[serde(default)]
+    pub include_unknown_created: bool,
+}
+
+impl UserFilter {
+    /// Builds the query listing the matching [Users](User)
+    /// 
+    /// The values are bound as `$created_after`, `$after` (only if `paginated`) and `$limit`.
+    fn query(&self, paginated: bool) -> String {
+        let mut conditions = Vec::new();
+
+        if let Some(has_email_password) = self.has_email_password {
+            conditions.push(format!(
+                "(count(<-authenticates[WHERE meta::id(in)[0] = '{}']) > 0) = {has_email_password}",
+                AuthMethodType::EmailPassword
+            ));
+        }
+
+        match self.disabled {
+            Some(true) => conditions.push("metadata.disabled != NONE".to_string()),
+            Some(false) => conditions.push("metadata.disabled = NONE".to_string()),
+            None => (),
+        }
+
+        if self.created_after.is_some() {
+            conditions.push(match self.include_unknown_created {
+                true => "(metadata.created > $created_after OR metadata.created = NONE)",
+                false => "metadata.created > $created_after",
+            }.to_string());
+        }
+
+        if paginated {
+            conditions.push("id > $after".to_string());
+        }
+
+        let mut query = String::from("SELECT * FROM user");
+
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+
+        query.push_str(" ORDER BY id LIMIT $limit;");
+        query
+    }
+}
+
+/// A page of [Users](User) returned by [User::list_filtered]
+#[derive(Clone, Debug, Serialize)]
+pub struct UserPage {
+    /// The [Users](User) in the page
+    pub users: Vec<User>,
+    /// The cursor of the next page (pass it as `after`), `None` if this is the last page
+    pub next: Option<Uuid>,
+}
+
 /// This struct is the database representation of the [User] struct.
 /// 
 /// It's necessary since the DB requires that the id is a [Thing], but
@@ -141,7 +279,7 @@ impl Builder for UserBuilder {
     fn build_safe(&self) -> Self::Buildable {
         Self::Buildable {
             id: match &self.id {
-                Some(id) => id.clone(),
+                Some(id) => *id,
                 None => Uuid::new_v4(),
             },
             attributes: match &self.attributes {
@@ -177,6 +315,43 @@ impl UserBuilder {
 }
 
 impl User {
+    /// Returns a serializable view of the [User] with the given [UserIdFormat]
+    pub fn with_id_format(&self, format: UserIdFormat) -> UserWithIdFormat<'_> {
+        UserWithIdFormat {
+            user: self,
+            format,
+        }
+    }
+
+    /// Time after creation during which a [User] is considered new
+    const NEW_USER_WINDOW: Duration = Duration::from_secs(5);
+
+    /// The reason recorded when a [User] is disabled because their last authentication method was transferred
+    const TRANSFERRED_REASON: &'static str = "all authentication methods were transferred to another account";
+
+    /// Returns how long ago the [User] registered
+    /// 
+    /// Returns `None` if the creation time is unknown (the account predates it being tracked)
+    pub fn age(&self) -> Option<Duration> {
+        self.metadata.created
+            .map(|created| Duration::from_secs(get_current_timestamp().saturating_sub(created)))
+    }
+
+    /// Whether the [User] registered within the given duration
+    /// 
+    /// Always `false` if the creation time is unknown
+    pub fn was_created_within(&self, duration: Duration) -> bool {
+        self.age().is_some_and(|age| age <= duration)
+    }
+
+    /// Whether the [User] was registered just now rather than being a returning user
+    /// 
+    /// This is a heuristic based on the creation timestamp, which is useful to
+    /// trigger onboarding flows right after authenticating.
+    pub fn is_new(&self) -> bool {
+        self.was_created_within(Self::NEW_USER_WINDOW)
+    }
+
     /// Sets the `disabled` flag in the [UserMetadata] struct
     /// 
     /// # Note:
@@ -202,27 +377,152 @@ impl User {
         self
     }
 
-    /// Saves the [User] to the database and associates it to the given auth method
-    pub async fn save(&self, db: &Surreal<Client>, credential: Box<dyn AuthMethod>) -> AuthResult<Self> {
-        Ok(db
+    /// Saves the [User] to the database and associates it to the given auth methods
+    /// 
+    /// All the credentials are created in a single transaction: if any of them
+    /// can't be associated, the [User] isn't created either.
+    /// 
+    /// The `registration_ip` (the client's IP address, if known) is stored in the [UserMetadata]
+    /// so that [get_by_registration_ip](Self::get_by_registration_ip) can find the [User].
+    /// Passing `None` keeps the metadata's own value.
+    /// 
+    /// # Concurrency
+    /// The duplicate check before the transaction only exists to return a clear error early:
+    /// two concurrent registrations with the same credential can both pass it. The credential's
+    /// record id is derived from its identifier (e.g. the email), so the second `CREATE` fails
+    /// and its whole transaction is cancelled, which surfaces as [AuthError::CredentialDuplicate].
+    /// Exactly one of the racing registrations succeeds, the caller can then treat the error
+    /// like any other duplicate registration.
+    #[tracing::instrument(skip(self, db, credentials), fields(user = %self.id), err)]
+    pub async fn save(&self, db: &Surreal<Client>, credentials: impl IntoIterator<Item = Box<dyn AuthMethod>>, registration_ip: Option<IpAddr>) -> AuthResult<Self> {
+        let credentials = credentials
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        if credentials.is_empty() {
+            return Err(AuthError::Invalid("At least one authentication method is required!".into()));
+        }
+
+        // A user can only have one auth method of each type (same as add_auth_method)
+        for (index, credential) in credentials.iter().enumerate() {
+            if credentials[..index].iter().any(|other| other.r#type() == credential.r#type()) {
+                return Err(AuthError::CredentialDuplicate("Cannot associate the same credential twice!".into()));
+            }
+        }
+
+        for credential in &credentials {
+            credential.check_registration(db).await?;
+        }
+
+        let credentials = credentials
+            .iter()
+            .map(|credential| credential.into_db())
+            .collect::<AuthResult<Vec<_>>>()?;
+
+        // Checks if any of the credentials is already linked to another user
+        for credential in &credentials {
+            if Self::auth_credential_exists(db, credential.as_ref()).await? {
+                return Err(AuthError::CredentialDuplicate("This authentication method is already linked to another user!".into()));
+            }
+        }
+
+        let mut user = self.clone();
+
+        if registration_ip.is_some() {
+            user.metadata.registration_ip = registration_ip;
+        }
+
+        // The user is created first so that its result is always at index 0
+        let mut query = String::from("
+            BEGIN TRANSACTION;
+                CREATE user CONTENT $user;
+        ");
+
+        for index in 0..credentials.len() {
+            query.push_str(&format!("
+                CREATE $credential_{index}.id;
+                RELATE ($credential_{index}.id)->authenticates->($user.id) CONTENT $credential_{index};
+                CREATE credential_audit CONTENT $audit_{index};
+            "));
+        }
+
+        query.push_str("
+                RETURN $user;
+            COMMIT TRANSACTION;
+        ");
+
+        let mut query = db
+            .query(query)
+            .bind(("user", DbUser::from(&user)));
+
+        for (index, credential) in credentials.into_iter().enumerate() {
+            query = query
+                .bind((format!("audit_{index}"), CredentialAudit::new(credential.id(), &user.id, CredentialAuditAction::Created, None)))
+                .bind((format!("credential_{index}"), credential));
+        }
+
+        let user: User = query
+            .await?
+            .take::<Option<DbUser>>(0)
+            .map_err(|_| AuthError::CredentialDuplicate("This user is already registered!".into()))?
+            .ok_or(AuthError::SaveFailed("Failed to save user!".into()))?
+            .into();
+
+        Ok(user)
+    }
+
+    /// Disables the [User]'s account for the given reasons and saves the change
+    /// 
+    /// All of the [User]'s sessions are revoked in the same transaction, so they're signed out everywhere.
+    /// The [User] is only changed once the transaction succeeds.
+    /// 
+    /// # Example
+    /// ```ignore
+    /// let session = user.create_auth_session(&db, AuthSessionState::Authenticated, None).await?;
+    /// 
+    /// user.disable(&db, vec!["due to suspicious activity".into()]).await?;
+    /// 
+    /// assert!(!AuthSession::is_active(&db, session).await?);
+    /// ```
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
+    pub async fn disable(&mut self, db: &Surreal<Client>, reasons: Vec<String>) -> AuthResult<Self> {
+        let mut disabled = self.clone();
+        disabled.disabled(Some(reasons));
+
+        let mut response = db
             .query("
                 BEGIN TRANSACTION;
-                    CREATE $credential.id;
-                    CREATE user CONTENT $user;
-                    RELATE ($credential.id)->authenticates->($user.id) CONTENT $credential;
-                    RETURN $user;
+                    UPDATE $user.id CONTENT $user RETURN AFTER;
+                    DELETE auth_session WHERE user = $user_id;
                 COMMIT TRANSACTION;
             ")
-            .bind(("credential", credential.into_db()?))
-            .bind(("user", DbUser::from(self)))
+            .bind(("user", DbUser::from(&disabled)))
+            .bind(("user_id", self.id.to_string()))
             .await?
-            .take::<Option<DbUser>>(0)
-            .map_err(|_| AuthError::CredentialDuplicate("This user is already registered!".into()))?
-            .ok_or(AuthError::SaveFailed("Failed to save user!".into()))?
-            .into())
+            .check()
+            .map_err(|_| AuthError::UpdateFailed("Failed to disable the user!".into()))?;
+
+        let user: User = response
+            .take::<Option<DbUser>>(0)?
+            .ok_or(AuthError::UpdateFailed("Failed to disable the user!".into()))?
+            .into();
+
+        tracing::info!(user = %self.id, "Disabled the user and revoked all auth sessions");
+
+        *self = disabled;
+        Ok(user)
+    }
+
+    /// Re-enables the [User]'s account and saves the change
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
+    pub async fn enable(&mut self, db: &Surreal<Client>) -> AuthResult<Self> {
+        self.disabled(None)
+            .update(db)
+            .await
     }
 
     /// Creates a new auth session
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn create_auth_session(&self, db: &Surreal<Client>, state: AuthSessionState, agent: Option<String>) -> AuthResult<AuthSessionId> {
         Ok(
             AuthSession::new(&self.id, state, agent)
@@ -233,6 +533,7 @@ impl User {
     }
 
     /// Applies any changes in the [User] struct by saving the in the DB
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn update(&self, db: &Surreal<Client>) -> AuthResult<Self> {
         Ok(db
             .query("UPDATE $user.id CONTENT $user RETURN AFTER;")
@@ -244,56 +545,205 @@ impl User {
             .into())
     }
 
+    #[tracing::instrument(skip_all, err)]
     pub async fn get_by_session_id(db: &Surreal<Client>, session_id: AuthSessionId) -> AuthResult<Self> {
 
         // Gets the session
         let session = AuthSession::get_by_id(db, session_id)
             .await?;
 
-        let user: User = db 
-            .query("SELECT * FROM $user_id;")
-            .bind(("user_id", session.user.to_string()))
+        // Rejects pending or expired sessions
+        if !session.is_valid() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        let user_id = Uuid::parse_str(&session.user)
+            .map_err(|_| AuthError::Unknown("The associated user doesn't exist!".into()))?;
+
+        Self::get_by_uuid_active(db, &user_id)
+            .await
+    }
+
+    /// Completes an authentication that returned [AuthStep::RequiresMfa](credential::AuthStep::RequiresMfa) using an MFA code
+    /// 
+    /// The pending session is consumed on success. If the code is incorrect an [AuthError::MfaRequired]
+    /// with the same pending session is returned, so the [User] can try again until the session expires.
+    /// After too many incorrect codes the session is revoked and [AuthError::MfaAttemptsExceeded] is returned.
+    #[tracing::instrument(skip_all, fields(method = %code.method), err)]
+    pub async fn complete_mfa(db: &Surreal<Client>, session_id: AuthSessionId, code: MfaCode) -> AuthResult<(Self, AuthSessionId)> {
+
+        // Gets the pending session
+        let pending = AuthSession::get_by_id(db, session_id.clone())
+            .await?;
+
+        if !pending.is_pending_mfa() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        let user_id = Uuid::parse_str(&pending.user)
+            .map_err(|_| AuthError::Unknown("The associated user doesn't exist!".into()))?;
+
+        let mut user = Self::get_by_uuid_active(db, &user_id)
+            .await?;
+
+        let mfa_credential = user
+            .get_mfa_credentials(db, Some(code.method))
             .await?
-            .take::<Option<DbUser>>(0)?
-            .ok_or(AuthError::Unknown("The associated user doesn't exist!".into()))?
-            .into();
+            .into_iter()
+            .next()
+            .ok_or(AuthError::CredentialNotFound("The MFA credential is incorrect!".into()))?;
 
-        Ok(user)
+        // Counts the attempt before checking the code, so codes can't be guessed indefinitely
+        let attempts_left = AuthSession::count_mfa_attempt(db, session_id.clone())
+            .await?
+            .ok_or(AuthError::MfaAttemptsExceeded)?;
+
+        // Verifies the code, which creates the authenticated session
+        let session = match mfa_credential.verify(&user, db, code.data).await {
+            Ok(session) => session,
+            Err(AuthError::MfaIncorrect) if attempts_left > 0 => {
+                return Err(AuthError::MfaRequired(session_id));
+            },
+            Err(AuthError::MfaIncorrect) => {
+                AuthSession::delete(db, session_id)
+                    .await?;
+
+                return Err(AuthError::MfaAttemptsExceeded);
+            },
+            Err(error) => return Err(error),
+        };
+
+        // The pending session can only be used once
+        AuthSession::delete(db, session_id)
+            .await?;
+
+        // Updates the last access timestamp
+        user.metadata.last_access = get_current_timestamp();
+        user
+            .update(db)
+            .await
+            .map_err(|_| AuthError::UpdateFailed("Failed to update last access!".into()))?;
+
+        Ok((user, session))
+    }
+
+    /// Checks whether the given credential already exists, which means it's linked to a [User]
+    async fn auth_credential_exists(db: &Surreal<Client>, credential: &dyn DbAuthMethod) -> AuthResult<bool> {
+        Ok(!db
+            .query("SELECT id FROM $credential_id;")
+            .bind(("credential_id", credential.id()))
+            .await?
+            .take::<Vec<Thing>>("id")?
+            .is_empty())
     }
 
     /// Associates a new authentication method with the [User]
+    #[tracing::instrument(skip(self, db, credential), fields(user = %self.id), err)]
     pub async fn add_auth_method(&self, db: &Surreal<Client>, credential: Box<dyn AuthMethod>) -> AuthResult<Self> {
+        credential.check_registration(db).await?;
 
         // Checks if this type of credential has already been associated
-        let already_associated = self
-            .get_auth_methods(db)
-            .await?
-            .iter()
-            .find(|&&method| method == credential.r#type())
-            .is_some();
-
-        if already_associated {
+        if self.has_auth_method(db, credential.r#type()).await? {
             return Err(AuthError::CredentialDuplicate("Cannot associate the same credential twice!".into()));
         }
 
-        Ok(db 
+        // Checks if the credential is already linked to another user
+        let credential = credential.into_db()?;
+
+        if Self::auth_credential_exists(db, credential.as_ref()).await? {
+            return Err(AuthError::CredentialDuplicate("This authentication method is already linked to another user!".into()));
+        }
+
+        let audit = CredentialAudit::new(credential.id(), &self.id, CredentialAuditAction::Created, None);
+
+        let user: User = db 
             .query("
                 BEGIN TRANSACTION;
                     CREATE $credential.id;
                     RELATE ($credential.id)->authenticates->($user.id) CONTENT $credential;
+                    CREATE credential_audit CONTENT $audit;
                     RETURN $user;
                 COMMIT TRANSACTION;
             ")
-            .bind(("credential", credential.into_db()?))
+            .bind(("credential", credential))
+            .bind(("audit", audit))
             .bind(("user", DbUser::from(self)))
             .await?
             .take::<Option<DbUser>>(0)
             .map_err(|_| AuthError::CredentialDuplicate("Cannot associate the same credential twice!".into()))?
             .ok_or(AuthError::UpdateFailed("Failed to add the authentication method!".into()))?
-            .into())
+            .into();
+
+        Ok(user)
+    }
+
+    /// Moves an authentication method from this [User] to another one (e.g. it was linked to the wrong account)
+    /// 
+    /// Returns the updated target [User]. The admin who made the transfer is recorded in the credential's audit log.
+    /// 
+    /// If it was this [User]'s only authentication method they couldn't sign in anymore,
+    /// so their account is disabled and their sessions are revoked in the same transaction.
+    #[tracing::instrument(skip(self, db, target), fields(user = %self.id, target = %target.id), err)]
+    pub async fn transfer_auth_method(&self, db: &Surreal<Client>, target: &User, auth_method_type: AuthMethodType, admin_id: &Uuid) -> AuthResult<Self> {
+        if self.id == target.id {
+            return Err(AuthError::Invalid("Cannot transfer an authentication method to the same user!".into()));
+        }
+
+        // Gets the auth method
+        let auth_methods = self
+            .get_auth_credentials(db, None)
+            .await?;
+
+        let credential = self
+            .get_auth_credentials(db, Some(auth_method_type))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(AuthError::CredentialNotFound("The authentication method could not be found!".into()))?;
+
+        // The user can't sign in without authentication methods
+        let disable_source = auth_methods.len() < 2;
+
+        // Checks if the target already has this type of credential
+        if target.has_auth_method(db, auth_method_type).await? {
+            return Err(AuthError::CredentialDuplicate("The target user already has this type of authentication method!".into()));
+        }
+
+        let audit = CredentialAudit::new(credential.id(), &target.id, CredentialAuditAction::Transferred, Some(admin_id));
+
+        let target: User = db
+            .query("
+                BEGIN TRANSACTION;
+                    DELETE authenticates WHERE in = $credential.id AND out = $user.id;
+                    RELATE ($credential.id)->authenticates->($target.id) CONTENT $credential;
+                    CREATE credential_audit CONTENT $audit;
+
+                    IF $disable_source {
+                        UPDATE $user.id SET metadata.disabled = $disabled_reasons;
+                        DELETE auth_session WHERE user = $user_id;
+                    };
+
+                    RETURN $target;
+                COMMIT TRANSACTION;
+            ")
+            .bind(("credential", credential))
+            .bind(("audit", audit))
+            .bind(("disable_source", disable_source))
+            .bind(("disabled_reasons", vec![Self::TRANSFERRED_REASON.to_string()]))
+            .bind(("user_id", self.id.to_string()))
+            .bind(("user", DbUser::from(self)))
+            .bind(("target", DbUser::from(target)))
+            .await?
+            .take::<Option<DbUser>>(0)
+            .map_err(|_| AuthError::UpdateFailed("Failed to transfer the authentication method!".into()))?
+            .ok_or(AuthError::UpdateFailed("Failed to transfer the authentication method!".into()))?
+            .into();
+
+        Ok(target)
     }
 
     /// Adds a new MFa method
+    #[tracing::instrument(skip(self, db, credential), fields(user = %self.id), err)]
     pub async fn add_mfa_method(&self, db: &Surreal<Client>, credential: Box<dyn MfaMethod>) -> AuthResult<Self> {
 
         // Checks if this MFA method has already been associated
@@ -308,26 +758,34 @@ impl User {
             return Err(AuthError::CredentialDuplicate("Cannot associated the same MFA method twice!".into()));
         }
 
-        Ok(db
+        let audit = CredentialAudit::new(credential.id(), &self.id, CredentialAuditAction::Created, None);
+
+        let user: User = db
             .query("
                 BEGIN TRANSACTION;
                     CREATE $mfa_method.id;
                     RELATE ($mfa_method.id)->verifies->($user.id) CONTENT $mfa_method;
+                    CREATE credential_audit CONTENT $audit;
                     RETURN $user;
                 COMMIT TRANSACTION;
             ")
             .bind(("mfa_method", credential))
+            .bind(("audit", audit))
             .bind(("user", DbUser::from(self)))
             .await?
             .take::<Option<DbUser>>(0)
             .map_err(|_| AuthError::CredentialDuplicate("Cannot associate the same MFA method twice!".into()))?
             .ok_or(AuthError::UpdateFailed("Failed to add the MFA method!".into()))?
-            .into()
-        )
+            .into();
+
+        Ok(user)
     }
 
     /// Deletes the [User] from the database
-    pub async fn delete(&self, db: &Surreal<Client>) -> AuthResult<()> {
+    /// 
+    /// Once the [User] is deleted, an [AuthEvent::UserDeleted] is emitted.
+    #[tracing::instrument(skip(self, db, emitter), fields(user = %self.id), err)]
+    pub async fn delete(&self, db: &Surreal<Client>, emitter: &dyn EventEmitter) -> AuthResult<()> {
         db
             .query("
                 BEGIN TRANSACTION;
@@ -346,7 +804,10 @@ impl User {
                 COMMIT TRANSACTION;
             ")
             .bind(("user_id", DbUser::from(self).id))
-            .await?;
+            .await?
+            .check()?;
+
+        emitter.emit(&AuthEvent::UserDeleted { user: self.id })?;
 
         Ok(())
     }
@@ -388,11 +849,31 @@ impl User {
             exp: timestamp + 300,
         };
 
-        Ok(Token::generate(&mfa_claims)?)
+        Token::generate(&mfa_claims)
+    }
+
+    /// Fetches a user by their UUID as long as their account isn't disabled
+    /// 
+    /// This should be used for every lookup made on behalf of the [User] themselves.
+    #[tracing::instrument(skip(db), err)]
+    pub async fn get_by_uuid_active(db: &Surreal<Client>, uuid: &Uuid) -> AuthResult<Self> {
+        let user = Self::get_by_uuid_any(db, uuid)
+            .await?;
+
+        // Checks if the user's account has been disabled
+        if let Some(reason) = user.metadata.disabled {
+            return Err(AuthError::UserDisabled(reason));
+        }
+
+        Ok(user)
     }
 
-    /// Fetches a user by their UUID
-    pub async fn get_by_uuid(db: &Surreal<Client>, uuid: &Uuid) -> AuthResult<Self> {
+    /// Fetches a user by their UUID regardless of their account's status
+    /// 
+    /// # Note:
+    /// This is meant for administrative lookups, use [get_by_uuid_active](Self::get_by_uuid_active) otherwise
+    #[tracing::instrument(skip(db), err)]
+    pub async fn get_by_uuid_any(db: &Surreal<Client>, uuid: &Uuid) -> AuthResult<Self> {
         Ok(db
             .query("SELECT * FROM $user_id;")
             .bind(("user_id", Thing::from(("user".to_string(), uuid.to_string()))))
@@ -402,19 +883,130 @@ impl User {
             .into())
     }
 
+    /// Fetches multiple users by their UUIDs in a single query, regardless of their account's status
+    /// 
+    /// UUIDs that don't belong to any [User] are skipped, so the map may have fewer entries than the input.
+    /// 
+    /// # Note:
+    /// This is meant for administrative lookups (e.g. resolving the owners of a list of sessions)
+    #[tracing::instrument(skip_all, fields(count = uuids.len()), err)]
+    pub async fn get_by_uuid_batch(db: &Surreal<Client>, uuids: &[Uuid]) -> AuthResult<HashMap<Uuid, Self>> {
+        if uuids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let user_ids = uuids
+            .iter()
+            .map(|uuid| Thing::from(("user".to_string(), uuid.to_string())))
+            .collect::<Vec<_>>();
+
+        Ok(db
+            .query("SELECT * FROM $user_ids;")
+            .bind(("user_ids", user_ids))
+            .await?
+            .take::<Vec<DbUser>>(0)?
+            .into_iter()
+            .map(|user| {
+                let user = User::from(user);
+                (user.id, user)
+            })
+            .collect())
+    }
+
+    /// Fetches every user registered from the given IP address, regardless of their account's status
+    /// 
+    /// # Note:
+    /// This is meant for fraud investigations (e.g. finding accounts mass-registered from the same host)
+    #[tracing::instrument(skip(db), err)]
+    pub async fn get_by_registration_ip(db: &Surreal<Client>, ip: IpAddr) -> AuthResult<Vec<Self>> {
+        Ok(db
+            .query("SELECT * FROM user WHERE metadata.registration_ip = $ip ORDER BY metadata.created ASC;")
+            .bind(("ip", ip))
+            .await?
+            .take::<Vec<DbUser>>(0)?
+            .into_iter()
+            .map(User::from)
+            .collect())
+    }
+
     /// Fetches the user that owns a given access token
+    #[tracing::instrument(skip_all, err)]
     pub async fn get_by_token(db: &Surreal<Client>, access_token: &Token) -> AuthResult<Self> {
 
         // Verifies the access token
-        let access_claims = Token::verify(&access_token, None)?;
+        let access_claims = Token::verify(access_token, None)?;
 
         // Fetches the user
-        Self::get_by_uuid(db, &access_claims.sub)
+        Self::get_by_uuid_active(db, &access_claims.sub)
             .await
     }
 
+    /// Lists the [Users](User) matching the [UserFilter], ordered by id
+    /// 
+    /// Results are paginated by passing the [next](UserPage::next) cursor of the
+    /// previous page as `after`.
+    #[tracing::instrument(skip(db), err)]
+    pub async fn list_filtered(db: &Surreal<Client>, filter: &UserFilter, after: Option<Uuid>, limit: usize) -> AuthResult<UserPage> {
+
+        // Fetches one extra user to know whether there's a next page
+        let mut users = db
+            .query(filter.query(after.is_some()))
+            .bind(("created_after", filter.created_after))
+            .bind(("after", after.map(|after| Thing::from(("user".to_string(), after.to_string())))))
+            .bind(("limit", limit + 1))
+            .await?
+            .take::<Vec<DbUser>>(0)?
+            .into_iter()
+            .map(User::from)
+            .collect::<Vec<_>>();
+
+        let next = match users.len() > limit {
+            true => {
+                users.truncate(limit);
+                users.last().map(|user| user.id)
+            },
+            false => None,
+        };
+
+        Ok(UserPage { users, next })
+    }
+
+    /// Fetches the user that owns a credential with the given identifier (e.g. an email),
+    /// regardless of the authentication method type
+    /// 
+    /// Every [AuthMethodType] is tried in order, and the first match is returned
+    /// along with the type of the credential that matched. The identifier is normalized
+    /// the same way the credential's is (e.g. emails are trimmed and lowercased).
+    #[tracing::instrument(skip(db), err)]
+    pub async fn find_by_any_credential(db: &Surreal<Client>, identifier: &str) -> AuthResult<Option<(Self, AuthMethodType)>> {
+        for method in AuthMethodType::iter() {
+            let user_id = match method {
+                AuthMethodType::EmailPassword => DbEmailPasswordMethod::find(db, &Email::normalized(identifier))
+                    .await?
+                    .and_then(|credential| credential.associated_user().cloned()),
+            };
+
+            let Some(user_id) = user_id else {
+                continue;
+            };
+
+            let user = db
+                .query("SELECT * FROM $user_id;")
+                .bind(("user_id", user_id))
+                .await?
+                .take::<Option<DbUser>>(0)?;
+
+            if let Some(user) = user {
+                return Ok(Some((user.into(), method)));
+            }
+        }
+
+        Ok(None)
+    }
+
     // TODO: REMOVE PANICS!!!
     /// Fetches the list of authentication methods associated with the [User]
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn get_auth_methods(&self, db: &Surreal<Client>) -> AuthResult<Vec<AuthMethodType>> {
         let auth_methods = db
             .query("SELECT in FROM ($user_id)<-authenticates;")
@@ -434,6 +1026,17 @@ impl User {
         Ok(auth_methods)
     }
 
+    /// Returns the number of authentication methods associated with the [User]
+    pub async fn count_auth_methods(&self, db: &Surreal<Client>) -> AuthResult<usize> {
+        Ok(self.get_auth_methods(db).await?.len())
+    }
+
+    /// Checks whether the given type of authentication method is associated with the [User]
+    pub async fn has_auth_method(&self, db: &Surreal<Client>, auth_method_type: AuthMethodType) -> AuthResult<bool> {
+        Ok(self.get_auth_methods(db).await?.contains(&auth_method_type))
+    }
+
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn get_auth_credentials(&self, db: &Surreal<Client>, method: Option<AuthMethodType>) -> AuthResult<Vec<Box<dyn DbAuthMethod>>> {
         let mut auth_credentials = db
             .query("SELECT * FROM ($user_id)<-authenticates;")
@@ -442,16 +1045,13 @@ impl User {
             .take::<Vec<Box<dyn DbAuthMethod>>>(0)?;
 
         if let Some(method) = method {
-            auth_credentials = auth_credentials
-                .into_iter()
-                .filter(|credential| match &credential.id().id {
-                    surrealdb::sql::Id::Array(array) => match &array.0[0] {
-                        Value::Strand(r#type) => AuthMethodType::from_str(r#type.as_str()).unwrap() == method,
-                        _ => false,
-                    }
+            auth_credentials.retain(|credential| match &credential.id().id {
+                surrealdb::sql::Id::Array(array) => match &array.0[0] {
+                    Value::Strand(r#type) => AuthMethodType::from_str(r#type.as_str()).unwrap() == method,
                     _ => false,
-                })
-                .collect();
+                }
+                _ => false,
+            });
         }
 
         Ok(auth_credentials)
@@ -459,6 +1059,7 @@ impl User {
 
     // TODO: REMOVE PANICS!!!
     /// Fetches the list multi-factor authentication methods associated with the [User] 
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn get_mfa_methods(&self, db: &Surreal<Client>) -> AuthResult<Vec<MfaMethodType>> {
         let mfa_methods = db
             .query("SELECT in FROM ($user_id)<-verifies;")
@@ -478,6 +1079,31 @@ impl User {
         Ok(mfa_methods)
     }
 
+    /// Whether the [User]'s account is past the [MfaPolicy]'s grace period
+    /// 
+    /// Accounts with an unknown creation time predate it being tracked,
+    /// so they're old enough for any policy.
+    pub fn is_past_mfa_grace_period(&self, policy: &MfaPolicy) -> bool {
+        self.age().is_none_or(|age| policy.is_enforced(age))
+    }
+
+    /// Checks whether the [MfaPolicy] requires the [User] to enroll an MFA method
+    /// 
+    /// This is the case when the account is past the policy's grace period
+    /// (see [is_past_mfa_grace_period](Self::is_past_mfa_grace_period)) and no MFA method has been associated yet.
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
+    pub async fn mfa_required(&self, db: &Surreal<Client>, policy: &MfaPolicy) -> AuthResult<bool> {
+        if !self.is_past_mfa_grace_period(policy) {
+            return Ok(false);
+        }
+
+        Ok(self
+            .get_mfa_methods(db)
+            .await?
+            .is_empty())
+    }
+
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn get_mfa_credentials(&self, db: &Surreal<Client>, method: Option<MfaMethodType>) -> AuthResult<Vec<Box<dyn MfaMethod>>> {
         let mut mfa_credentials = db
             .query("SELECT * FROM ($user_id)<-verifies;")
@@ -486,73 +1112,263 @@ impl User {
             .take::<Vec<Box<dyn MfaMethod>>>(0)?;
 
         if let Some(method) = method {
-            mfa_credentials = mfa_credentials
-                .into_iter()
-                .filter(|credential| match &credential.id().id {
-                    surrealdb::sql::Id::Array(array) => match &array.0[0] {
-                        Value::Strand(r#type) => MfaMethodType::from_str(r#type.as_str()).unwrap() == method,
-                        _ => false,
-                    }
+            mfa_credentials.retain(|credential| match &credential.id().id {
+                surrealdb::sql::Id::Array(array) => match &array.0[0] {
+                    Value::Strand(r#type) => MfaMethodType::from_str(r#type.as_str()).unwrap() == method,
                     _ => false,
-                })
-                .collect();
+                }
+                _ => false,
+            });
         }
 
         Ok(mfa_credentials)
     }
 
-    /// Deletes a given authentication method as long as it's associated with the [User]
+    /// Deletes a given MFA method if it's associated with the [User]
+    /// 
+    /// Removing a method that isn't associated (e.g. it was already removed) succeeds
+    /// without doing anything, since the method is gone either way.
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn remove_mfa_method(&self, db: &Surreal<Client>, mfa_method_type: MfaMethodType) -> AuthResult<Self> {
 
         // Gets the associated MFA method
         let mfa_methods = self
             .get_mfa_credentials(db, Some(mfa_method_type))
             .await?;
-        let mfa_method = mfa_methods.get(0);
 
-        if let Some(mfa_method) = mfa_method {
+        if let Some(mfa_method) = mfa_methods.first() {
 
             // Deletes the MFA method
             db
-                .query("DELETE $mfa_method_id;")
+                .query("
+                    BEGIN TRANSACTION;
+                        DELETE $mfa_method_id;
+                        CREATE credential_audit CONTENT $audit;
+                    COMMIT TRANSACTION;
+                ")
                 .bind(("mfa_method_id", mfa_method.id()))
-                .await?;
-        } else {
-
-            // The MFA method doesn't exist
-            return Err(AuthError::CredentialNotFound("The MFA method couldn't be found or doesn't exist".into()));
+                .bind(("audit", CredentialAudit::new(mfa_method.id(), &self.id, CredentialAuditAction::Deleted, None)))
+                .await?
+                .check()?;
         }
 
-        
         Ok(self.clone())
     }
 
-    /// Deletes a given authentication method as long as it's associated with the [User] and it's not the only credential
+    /// Deletes a given authentication method if it's associated with the [User] and it's not the only credential
+    /// 
+    /// Removing a method that isn't associated (e.g. it was already removed) succeeds
+    /// without doing anything, since the method is gone either way.
+    #[tracing::instrument(skip(self, db), fields(user = %self.id), err)]
     pub async fn remove_auth_method(&self, db: &Surreal<Client>, auth_method_type: AuthMethodType) -> AuthResult<Self> {
 
         // Gets the auth method
         let auth_methods = self
             .get_auth_credentials(db, Some(auth_method_type))
             .await?;
-        let auth_method = auth_methods.get(0);
 
-        if let Some(auth_method) = auth_method {
+        if let Some(auth_method) = auth_methods.first() {
 
             // Cannot delete the user's only authentication method
-            if auth_methods.len() > 1 {
+            if self.count_auth_methods(db).await? > 1 {
 
                 // Deletes the credential
                 db 
-                    .query("DELETE $auth_method_id;")
+                    .query("
+                        BEGIN TRANSACTION;
+                            DELETE $auth_method_id;
+                            CREATE credential_audit CONTENT $audit;
+                        COMMIT TRANSACTION;
+                    ")
                     .bind(("auth_method_id", auth_method.id()))
-                    .await?;
+                    .bind(("audit", CredentialAudit::new(auth_method.id(), &self.id, CredentialAuditAction::Deleted, None)))
+                    .await?
+                    .check()?;
             } else {
-                return Err(AuthError::CredentialOnly("Cannot remove the only authentication method!".into()));
+                return Err(AuthError::CredentialOnly {
+                    credential: auth_method.id(),
+                    credential_type: auth_method_type,
+                });
             }   
-        } else {
-            return Err(AuthError::CredentialNotFound("The authentication method couldn't be found or doesn't exist!".into()));
         }
 
         Ok(self.clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{User, UserFilter, UserIdFormat, UserMetadata};
+    use super::credential::{email_password::EmailPasswordMethod, AuthMethod, AuthMethodType};
+    use super::{email::Email, password::Password};
+    use crate::{builder::*, mfa_policy::MfaPolicy, prelude::*};
+    use jsonwebtoken::get_current_timestamp;
+    use surrealdb::{engine::remote::ws::Client, Surreal};
+    use uuid::Uuid;
+
+    const ID: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    fn user() -> User {
+        User::builder()
+            .id(Uuid::parse_str(ID).unwrap())
+            .build_safe()
+    }
+
+    #[test]
+    fn user_is_new() {
+        assert!(user().is_new())
+    }
+
+    #[test]
+    fn user_is_not_new() {
+        let user = User::builder()
+            .metadata(UserMetadata::builder().created(0).build_safe())
+            .build_safe();
+
+        assert!(!user.is_new())
+    }
+
+    #[test]
+    fn user_was_created_within() {
+        let user = User::builder()
+            .metadata(UserMetadata::builder().created(get_current_timestamp() - 60).build_safe())
+            .build_safe();
+
+        assert!(user.was_created_within(Duration::from_secs(120)));
+        assert!(!user.was_created_within(Duration::from_secs(30)))
+    }
+
+    #[test]
+    fn user_unknown_age() {
+        let mut user = user();
+        user.metadata.created = None;
+
+        assert_eq!(user.age(), None);
+        assert!(!user.is_new())
+    }
+
+    const MFA_POLICY: MfaPolicy = MfaPolicy {
+        require_after_days: 30,
+        grace_period_days: 7,
+    };
+
+    #[test]
+    fn user_past_mfa_grace_period() {
+        let user = User::builder()
+            .metadata(UserMetadata::builder().created(0).build_safe())
+            .build_safe();
+
+        assert!(user.is_past_mfa_grace_period(&MFA_POLICY))
+    }
+
+    #[test]
+    fn user_within_mfa_grace_period() {
+        assert!(!user().is_past_mfa_grace_period(&MFA_POLICY))
+    }
+
+    #[test]
+    fn user_unknown_age_past_mfa_grace_period() {
+        let mut user = user();
+        user.metadata.created = None;
+
+        assert!(user.is_past_mfa_grace_period(&MFA_POLICY))
+    }
+
+    #[test]
+    fn user_serialize_hyphenated() {
+        let user = serde_json::to_value(user()).unwrap();
+
+        assert_eq!(user["id"], ID)
+    }
+
+    #[test]
+    fn user_serialize_urn() {
+        let user = serde_json::to_value(user().with_id_format(UserIdFormat::Urn)).unwrap();
+
+        assert_eq!(user["id"], format!("urn:uuid:{ID}"))
+    }
+
+    #[test]
+    fn user_serialize_simple() {
+        let user = serde_json::to_value(user().with_id_format(UserIdFormat::Simple)).unwrap();
+
+        assert_eq!(user["id"], ID.replace('-', ""))
+    }
+
+    fn credential() -> Box<dyn AuthMethod> {
+        Box::new(EmailPasswordMethod::new(
+            Email::new("email@example.com").unwrap(),
+            Password::new("Sup3r_S3cure_P4ssword".into()),
+        ))
+    }
+
+    // Both checks run before the DB is used, so the client is never connected
+    #[tokio::test]
+    async fn user_save_without_credentials() {
+        let result = user().save(&Surreal::<Client>::init(), Vec::new(), None).await;
+
+        assert!(matches!(result, Err(AuthError::Invalid(_))))
+    }
+
+    #[tokio::test]
+    async fn user_save_repeated_credential_types() {
+        let result = user().save(&Surreal::<Client>::init(), vec![credential(), credential()], None).await;
+
+        assert!(matches!(result, Err(AuthError::CredentialDuplicate(_))))
+    }
+
+    #[test]
+    fn user_filter_query_empty() {
+        assert_eq!(UserFilter::default().query(false), "SELECT * FROM user ORDER BY id LIMIT $limit;")
+    }
+
+    #[test]
+    fn user_filter_query() {
+        let filter = UserFilter {
+            has_email_password: Some(false),
+            disabled: Some(true),
+            created_after: Some(0),
+            include_unknown_created: false,
+        };
+
+        assert_eq!(
+            filter.query(true),
+            "SELECT * FROM user WHERE (count(<-authenticates[WHERE meta::id(in)[0] = 'EmailPassword']) > 0) = false \
+                AND metadata.disabled != NONE AND metadata.created > $created_after AND id > $after ORDER BY id LIMIT $limit;"
+        )
+    }
+
+    #[test]
+    fn user_filter_query_unknown_created() {
+        let filter = UserFilter {
+            created_after: Some(0),
+            include_unknown_created: true,
+            ..Default::default()
+        };
+
+        assert!(filter.query(false).contains("(metadata.created > $created_after OR metadata.created = NONE)"))
+    }
+
+    // The check runs before the DB is used, so the client is never connected
+    #[tokio::test]
+    async fn user_transfer_auth_method_to_self() {
+        let user = user();
+        let result = user
+            .transfer_auth_method(&Surreal::<Client>::init(), &user, AuthMethodType::EmailPassword, &Uuid::new_v4())
+            .await;
+
+        assert!(matches!(result, Err(AuthError::Invalid(_))))
+    }
+
+    // The query fails since the client is never connected
+    #[tokio::test]
+    async fn user_disable_failed_keeps_user() {
+        let mut user = user();
+        let result = user.disable(&Surreal::<Client>::init(), vec!["due to inactivity (12 months)".into()]).await;
+
+        assert!(result.is_err());
+        assert_eq!(user.metadata.disabled, None)
+    }
+}
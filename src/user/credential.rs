@@ -1,12 +1,25 @@
+use argon2::{Algorithm, Argon2, Params, Version};
 use serde::{Deserialize, Serialize};
 use surrealdb::{engine::remote::ws::Client, sql::Thing, Surreal};
 use crate::{prelude::*, session::AuthSessionId};
-use strum_macros::{EnumString, Display};
+use strum_macros::{EnumIter, EnumString, Display};
 
 /// Email/password authentication
 pub mod email_password;
 /// TOTP MFA authentication
 pub mod totp;
+/// Email OTP MFA authentication
+pub mod email_otp;
+/// Credential change history
+pub mod audit;
+
+/// Returns the hasher used for every password and code stored by the crate
+/// 
+/// The algorithm is pinned to Argon2id (v0x13) as recommended by OWASP, rather than
+/// relying on whatever [Argon2::default] resolves to in the pinned crate version.
+pub(crate) fn argon2() -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+}
 
 #[typetag::serde(tag = "type")]
 pub trait DbAuthMethod: std::fmt::Debug + Send + Sync {
@@ -18,9 +31,22 @@ pub trait DbAuthMethod: std::fmt::Debug + Send + Sync {
     /// while the second string is the actual method identifier (which is usually the [User]'s email).
     /// This is because different credential types tend to have the same identifier.
     fn id(&self) -> Thing;
+
+    /// Whether the credential was provisioned by an admin rather than the [User] themselves
+    fn created_by_admin(&self) -> bool {
+        false
+    }
+
+    /// Whether the [User] should be asked to replace the credential's secret (e.g. their password)
+    /// 
+    /// Admin-provisioned credentials use a secret the admin knows, so they require a change until the [User] picks
+    /// their own (see [EmailPasswordMethod::change_password](email_password::EmailPasswordMethod::change_password)).
+    fn requires_password_change(&self) -> bool {
+        self.created_by_admin()
+    }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, EnumString, Display, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, EnumString, EnumIter, Display, PartialEq)]
 pub enum AuthMethodType {
     EmailPassword,
 }
@@ -28,42 +54,85 @@ pub enum AuthMethodType {
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, EnumString, Display, PartialEq)]
 pub enum MfaMethodType {
     Totp,
+    EmailOtp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MfaCode {
     pub method: MfaMethodType,
     pub data: String,
 }
 
+/// The outcome of a successful [AuthMethod::authenticate] call
+#[derive(Debug)]
+pub enum AuthStep {
+    /// The [User] is fully authenticated
+    Complete {
+        user: User,
+        session: AuthSessionId,
+        /// Whether the [User] should be asked to change their password (see [DbAuthMethod::requires_password_change])
+        requires_password_change: bool,
+    },
+    /// The credential is correct but the [User] has MFA enabled: the pending session
+    /// must be completed with [User::complete_mfa](crate::user::User::complete_mfa) 
+    /// (within 5 minutes) using one of the listed methods
+    RequiresMfa {
+        session: AuthSessionId,
+        methods: Vec<MfaMethodType>,
+        /// Whether the [User] should be asked to change their password once MFA is completed
+        requires_password_change: bool,
+    },
+}
+
 /// Defines all primary credential types
 #[async_trait::async_trait]
 #[typetag::serde(tag = "type")]
-pub trait AuthMethod: std::fmt::Debug {
+pub trait AuthMethod: std::fmt::Debug + Send + Sync {
 
     /// Returns the type of authentication method
     fn r#type(&self) -> AuthMethodType;
 
     // /// Returns the restructured credential that should be inserted into the DB
+    #[allow(clippy::wrong_self_convention)]
     fn into_db(&self) -> AuthResult<Box<dyn DbAuthMethod>>; 
 
+    /// Checks that the credential can be registered (e.g. its password went through a policy)
+    /// 
+    /// This is called by [User::save](crate::user::User::save) and 
+    /// [User::add_auth_method](crate::user::User::add_auth_method) before anything is stored.
+    async fn check_registration(&self, _db: &Surreal<Client>) -> AuthResult<()> {
+        Ok(())
+    }
+
     /// Uses the credentials provided to authenticate the [User]. If the credential values are correct, the 
-    /// function will return the [User] associated with it
+    /// function will return the [User] associated with it, or a pending session if the [User] has MFA enabled
+    /// 
+    /// MFA codes are only checked by [User::complete_mfa](crate::user::User::complete_mfa), so every
+    /// code counts against the pending session's attempts.
     /// 
     /// # Example
     /// ```ignore
-    /// use auth::user::credential::EmailPasswordCredential;
+    /// use auth::user::{credential::{email_password::*, AuthStep}, email::Email, password::Password};
     /// use auth::prelude::*;
     /// 
-    /// let user = EmailPasswordCredential::new(
-    ///         "email@example.com".into(),
-    ///         "Sup3r_S3cure_P4ssword".into()
+    /// let step = EmailPasswordMethod::new(
+    ///         Email::new("email@example.com")?,
+    ///         Password::new("Sup3r_S3cure_P4ssword".into())
     ///     )
     ///     .authenticate(&db)
-    ///     .await
-    ///     .unwrap();
+    ///     .await?;
+    /// 
+    /// let (user, session, requires_password_change) = match step {
+    ///     AuthStep::Complete { user, session, requires_password_change } => (user, session, requires_password_change),
+    ///     AuthStep::RequiresMfa { session, methods, requires_password_change } => {
+    ///         // Ask the user for a code of one of the `methods`
+    ///         let (user, session) = User::complete_mfa(&db, session, code).await?;
+    ///         (user, session, requires_password_change)
+    ///     }
+    /// };
     /// ```
-    async fn authenticate(&self, db: &Surreal<Client>, mfa: Option<MfaCode>) -> AuthResult<(User, AuthSessionId)>;
+    async fn authenticate(&self, db: &Surreal<Client>) -> AuthResult<AuthStep>;
 }
 
 #[async_trait::async_trait]
@@ -71,5 +140,10 @@ pub trait AuthMethod: std::fmt::Debug {
 pub trait MfaMethod: std::fmt::Debug + Send {
     fn id(&self) -> Thing; 
     fn r#type(&self) -> MfaMethodType;
+
+    /// Checks the code and creates an authenticated session for the [User] if it's correct
+    /// 
+    /// An incorrect code returns [AuthError::MfaIncorrect](crate::prelude::AuthError::MfaIncorrect)
+    /// without creating any session, the caller decides whether the [User] can try again.
     async fn verify(&self, user: &User, db: &Surreal<Client>, input: String) -> AuthResult<AuthSessionId>;
 }
\ No newline at end of file
@@ -0,0 +1,68 @@
+use jsonwebtoken::get_current_timestamp;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use surrealdb::{engine::remote::ws::Client, sql::Thing, Surreal};
+use uuid::Uuid;
+
+use crate::prelude::*;
+
+/// The kind of change recorded by a [CredentialAudit] entry
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, EnumString, Display, PartialEq)]
+pub enum CredentialAuditAction {
+    /// The credential was created and associated with the [User]
+    Created,
+    /// The credential was removed from the [User]
+    Deleted,
+    /// The credential was moved to the [User] from another account
+    Transferred,
+}
+
+/// A single change to a credential
+///
+/// Entries are stored in the `credential_audit` table and never contain any
+/// credential data (hashes, secrets, etc.), only who changed which credential and when.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CredentialAudit {
+    /// The id of the credential that was changed
+    pub credential: Thing,
+    /// The id of the [User] the credential belongs to (after the change)
+    pub user: String,
+    /// What happened to the credential
+    pub action: CredentialAuditAction,
+    /// The id of the [User] who made the change, `None` if it was made by
+    /// the owner themselves or the actor is unknown
+    pub changed_by: Option<String>,
+    /// When the change happened
+    pub created: u64,
+}
+
+impl CredentialAudit {
+    /// Creates a new audit entry
+    /// 
+    /// The entry isn't stored here: it's meant to be bound to the query that changes the credential
+    /// and created with `CREATE credential_audit CONTENT $audit` inside the same transaction,
+    /// so a change is never committed without its entry (or the other way around).
+    pub fn new(
+        credential: Thing,
+        user_id: &Uuid,
+        action: CredentialAuditAction,
+        changed_by: Option<&Uuid>,
+    ) -> Self {
+        Self {
+            credential,
+            user: user_id.to_string(),
+            action,
+            changed_by: changed_by.map(Uuid::to_string),
+            created: get_current_timestamp(),
+        }
+    }
+
+    /// Fetches every audit entry of the given credential, oldest first
+    pub async fn get_by_credential(db: &Surreal<Client>, credential: Thing) -> AuthResult<Vec<Self>> {
+        Ok(db
+            .query("SELECT * FROM credential_audit WHERE credential = $credential ORDER BY created ASC;")
+            .bind(("credential", credential))
+            .await?
+            .take::<Vec<Self>>(0)?)
+    }
+}
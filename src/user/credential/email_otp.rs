@@ -0,0 +1,194 @@
+use std::fmt;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    PasswordHash, PasswordHasher, PasswordVerifier,
+};
+use jsonwebtoken::get_current_timestamp;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use surrealdb::{engine::remote::ws::Client, sql::{Id, Thing}, Surreal};
+use uuid::Uuid;
+use crate::{mailer::Mailer, prelude::*, session::{AuthSessionId, AuthSessionState}, user::email::Email};
+use super::{argon2, MfaMethod, MfaMethodType};
+
+/// The Email OTP MFA method
+///
+/// A lightweight alternative to TOTP for users who can't use an authenticator app:
+/// a short numeric code is generated on demand and sent to the [User]'s email.
+/// Only the hash of the pending code is stored.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EmailOtpMethod {
+    #[serde(rename(deserialize = "in"))]
+    id: Thing,
+    /// The email the codes are sent to
+    #[serde(deserialize_with = "Email::deserialize_normalized")]
+    pub email: Email,
+    /// The hash of the pending code (if any)
+    code: Option<String>,
+    /// The expiry timestamp of the pending code
+    expires: u64,
+}
+
+impl fmt::Debug for EmailOtpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmailOtpMethod")
+            .field("id", &self.id)
+            .field("email", &self.email)
+            .field("code", &self.code.as_ref().map(|_| "[REDACTED]"))
+            .field("expires", &self.expires)
+            .finish()
+    }
+}
+
+impl EmailOtpMethod {
+    const CODE_LENGTH: usize = 6;
+    const CODE_DURATION: u64 = 600;
+
+    /// The MFA method's constructor
+    pub fn new(user_id: Uuid, email: Email) -> Self {
+        Self {
+            id: Thing::from((
+                "credential".to_string(),
+                Id::Array(vec![MfaMethodType::EmailOtp.to_string(), user_id.to_string()].into())
+            )),
+            email,
+            code: None,
+            expires: 0,
+        }
+    }
+
+    /// Returns a random numeric code, zero-padded to [CODE_LENGTH](Self::CODE_LENGTH) digits
+    fn random_code() -> String {
+        format!(
+            "{:0width$}",
+            rand::thread_rng().gen_range(0..10u32.pow(Self::CODE_LENGTH as u32)),
+            width = Self::CODE_LENGTH
+        )
+    }
+
+    /// Generates a new code and stores its hash, replacing any pending one
+    ///
+    /// The returned plaintext code should be sent to the [User]'s email,
+    /// it expires after 10 minutes. Fails with [AuthError::CredentialNotFound]
+    /// if the method isn't associated with any [User].
+    #[tracing::instrument(skip_all, err)]
+    pub async fn generate(&mut self, db: &Surreal<Client>) -> AuthResult<String> {
+        let code = Self::random_code();
+
+        let hash = argon2()
+            .hash_password(code.as_bytes(), &SaltString::generate(&mut OsRng))?
+            .to_string();
+        let expires = get_current_timestamp() + Self::CODE_DURATION;
+
+        // Stores the hash, no edge is updated if the method was never associated
+        let updated = db
+            .query("UPDATE verifies SET code = $code, expires = $expires WHERE in = $mfa_method_id RETURN AFTER;")
+            .bind(("code", &hash))
+            .bind(("expires", expires))
+            .bind(("mfa_method_id", &self.id))
+            .await?
+            .take::<Option<Thing>>((0, "id"))?;
+
+        if updated.is_none() {
+            return Err(AuthError::CredentialNotFound("The MFA method isn't associated with any user!".into()));
+        }
+
+        self.code = Some(hash);
+        self.expires = expires;
+
+        Ok(code)
+    }
+
+    /// Generates a new code and sends it to the [User]'s email with the given [Mailer]
+    #[tracing::instrument(skip_all, err)]
+    pub async fn send(&mut self, db: &Surreal<Client>, mailer: &dyn Mailer) -> AuthResult<()> {
+        let code = self
+            .generate(db)
+            .await?;
+
+        mailer.send_mfa_code(&self.email, &code)
+    }
+
+    /// Checks the given code against the pending one, which is consumed if it matches
+    async fn consume(&self, db: &Surreal<Client>, code: &str) -> AuthResult<bool> {
+        let hash = match &self.code {
+            Some(hash) if get_current_timestamp() <= self.expires => hash,
+            _ => return Ok(false),
+        };
+
+        let valid = argon2()
+            .verify_password(code.as_bytes(), &PasswordHash::new(hash)?)
+            .is_ok();
+
+        if !valid {
+            return Ok(false);
+        }
+
+        // Marks the code as used, only if it's still the pending one: if a concurrent
+        // verification already consumed it, no edge is updated and the code is rejected
+        let consumed = db
+            .query("UPDATE verifies SET code = NONE WHERE in = $mfa_method_id AND code = $code RETURN BEFORE;")
+            .bind(("mfa_method_id", &self.id))
+            .bind(("code", hash))
+            .await?
+            .take::<Vec<Thing>>((0, "id"))?;
+
+        Ok(!consumed.is_empty())
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl MfaMethod for EmailOtpMethod {
+    fn id(&self) -> Thing {
+        self.id.clone()
+    }
+
+    fn r#type(&self) -> MfaMethodType {
+        MfaMethodType::EmailOtp
+    }
+
+    #[tracing::instrument(skip_all, fields(user = %user.id), err)]
+    async fn verify(&self, user: &User, db: &Surreal<Client>, code: String) -> AuthResult<AuthSessionId> {
+        if self.consume(db, code.as_str()).await? {
+
+            // Creates the auth session
+            let session = user
+                .create_auth_session(db, AuthSessionState::Authenticated, None)
+                .await?;
+
+            Ok(session)
+        } else {
+            Err(AuthError::MfaIncorrect)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EmailOtpMethod;
+    use crate::user::email::Email;
+    use uuid::Uuid;
+
+    #[test]
+    fn email_otp_code_format() {
+        for _ in 0..100 {
+            let code = EmailOtpMethod::random_code();
+
+            assert_eq!(code.len(), EmailOtpMethod::CODE_LENGTH);
+            assert!(code.chars().all(|char| char.is_ascii_digit()))
+        }
+    }
+
+    #[test]
+    fn email_otp_debug_redacted() {
+        let mut otp = EmailOtpMethod::new(Uuid::new_v4(), Email::new("email@example.com").unwrap());
+        otp.code = Some("hash".into());
+
+        let debug = format!("{otp:?}");
+
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("hash"))
+    }
+}
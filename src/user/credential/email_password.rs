@@ -1,26 +1,49 @@
-use super::{AuthMethod, AuthMethodType, DbAuthMethod, MfaCode};
-use crate::{prelude::*, session::{AuthSessionId, AuthSessionState}};
+use super::{argon2, AuthMethod, AuthMethodType, AuthStep, DbAuthMethod};
+use crate::{prelude::*, session::AuthSessionState};
 use argon2::{ 
     password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    PasswordHash, PasswordHasher, PasswordVerifier,
 };
 use serde::{Deserialize, Serialize};
-use crate::user::DbUser;
+use crate::user::{email::Email, password::Password, DbUser};
+use std::{collections::HashMap, fmt, sync::OnceLock};
 use surrealdb::{
     engine::remote::ws::Client,
     sql::{Id, Thing},
     Surreal,
 };
 
+/// A hash of a throwaway password, verified against when the credential doesn't exist
+/// so that both failure paths take the same amount of time (prevents user enumeration)
+static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+/// Returns the [DUMMY_HASH], computing it on first use
+fn dummy_hash() -> &'static str {
+    DUMMY_HASH.get_or_init(|| {
+        argon2()
+            .hash_password(b"dummy_password", &SaltString::generate(&mut OsRng))
+            .expect("Failed to calculate the dummy hash!")
+            .to_string()
+    })
+}
+
 /// The Email/Password auth method (the oldest trick in the book lol)
 /// 
 /// This allows a [User] to authenticate via their email and password.
 /// The password is hashed with Argon2.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct EmailPasswordMethod {
     /// The user's email
-    pub email: String,
-    password: String,
+    pub email: Email,
+    /// Never serialized, so a serialized credential can't be deserialized back
+    /// (and registered) with a placeholder instead of the password
+    #[serde(skip_serializing)]
+    password: Password,
+    #[serde(skip)]
+    created_by_admin: bool,
+    #[serde(skip)]
+    policy_checked: bool,
 }
 
 /// The database representation of the Email/Password auth 
@@ -29,13 +52,118 @@ pub struct EmailPasswordMethod {
 ///    back to an [EmailPasswordMethod]
 ///  - It contains a SurrealDb id
 ///  - It contains the owner's ID
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DbEmailPasswordMethod {
     #[serde(rename(deserialize = "in"))]
     pub id: Thing,
     data: String,
     #[serde(skip_serializing, rename(deserialize = "out"))]
     associated_user: Option<Thing>,
+    #[serde(default)]
+    created_by_admin: bool,
+}
+
+impl fmt::Debug for DbEmailPasswordMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DbEmailPasswordMethod")
+            .field("id", &self.id)
+            .field("data", &"[REDACTED]")
+            .field("associated_user", &self.associated_user)
+            .field("created_by_admin", &self.created_by_admin)
+            .finish()
+    }
+}
+
+impl DbEmailPasswordMethod {
+    /// Returns the id of the credential stored under the given email
+    fn id_for(email: &str) -> Thing {
+        Thing::from((
+            "credential".to_string(),
+            Id::Array(vec![AuthMethodType::EmailPassword.to_string(), email.to_string()].into()),
+        ))
+    }
+
+    /// Fetches the credential registered with the given email (if it exists)
+    /// 
+    /// Credentials registered before emails were normalized are stored under the email exactly
+    /// as it was typed, so that id is looked up too. Both lookups always run, so the timing doesn't
+    /// depend on which one matches.
+    pub(crate) async fn find(db: &Surreal<Client>, email: &Email) -> AuthResult<Option<Self>> {
+        let mut response = db
+            .query("
+                SELECT * FROM $credential_id->authenticates;
+                SELECT * FROM $raw_credential_id->authenticates;
+            ")
+            .bind(("credential_id", Self::id_for(email)))
+            .bind(("raw_credential_id", Self::id_for(email.raw())))
+            .await?;
+
+        let credential = response.take::<Option<Self>>(0)?;
+        let raw_credential = response.take::<Option<Self>>(1)?;
+
+        Ok(credential.or(raw_credential))
+    }
+
+    /// Checks whether any credential is registered with the given email, ignoring case
+    /// 
+    /// Unlike [find](Self::find) this catches credentials registered before emails were normalized
+    /// with a different casing, but it scans every credential, so it's only used for registration.
+    pub(crate) async fn exists_ignoring_case(db: &Surreal<Client>, email: &Email) -> AuthResult<bool> {
+        Ok(!db
+            .query("
+                SELECT id FROM authenticates 
+                WHERE meta::id(in)[0] = $type AND string::lowercase(string::trim(meta::id(in)[1])) = $email
+                LIMIT 1;
+            ")
+            .bind(("type", AuthMethodType::EmailPassword.to_string()))
+            .bind(("email", email.to_string()))
+            .await?
+            .take::<Vec<Thing>>("id")?
+            .is_empty())
+    }
+
+    /// Returns the id of the [User] the credential belongs to
+    pub(crate) fn associated_user(&self) -> Option<&Thing> {
+        self.associated_user.as_ref()
+    }
+
+    /// Checks that the stored hash is a well-formed PHC string
+    /// 
+    /// A malformed hash means the credential was corrupted or migrated
+    /// incorrectly, which would otherwise surface as a generic hash error
+    /// during authentication.
+    pub fn validate_hash(&self) -> AuthResult<()> {
+        PasswordHash::new(&self.data)
+            .map_err(|_| {
+                tracing::error!(credential = %self.id, "Stored hash is malformed");
+                AuthError::Invalid("The stored credential is corrupted!".into())
+            })?;
+
+        Ok(())
+    }
+
+    /// Replaces the stored hash with the given password's
+    /// 
+    /// The [User] chose the new password, so the credential no longer counts as admin-provisioned
+    /// and [requires_password_change](DbAuthMethod::requires_password_change) is cleared.
+    pub(crate) async fn update_password(&self, db: &Surreal<Client>, password: &Password) -> AuthResult<()> {
+        let data = argon2()
+            .hash_password(password.expose_secret().as_bytes(), &SaltString::generate(&mut OsRng))?
+            .to_string();
+
+        let updated = db
+            .query("UPDATE authenticates SET data = $data, created_by_admin = false WHERE in = $credential_id RETURN AFTER;")
+            .bind(("data", data))
+            .bind(("credential_id", &self.id))
+            .await?
+            .take::<Option<Thing>>((0, "id"))?;
+
+        if updated.is_none() {
+            return Err(AuthError::CredentialNotFound("The email or password is incorrect!".into()));
+        }
+
+        Ok(())
+    }
 }
 
 #[typetag::serde]
@@ -43,12 +171,196 @@ impl DbAuthMethod for DbEmailPasswordMethod {
     fn id(&self) -> Thing {
         self.id.clone()
     }
+
+    fn created_by_admin(&self) -> bool {
+        self.created_by_admin
+    }
+}
+
+/// The password requirements enforced by [EmailPasswordMethod::validated]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EmailPasswordPolicy {
+    /// The minimum number of characters
+    pub min_length: usize,
+    /// The maximum number of characters
+    pub max_length: usize,
+    /// Whether at least one uppercase letter is required
+    pub require_uppercase: bool,
+    /// Whether at least one lowercase letter is required
+    pub require_lowercase: bool,
+    /// Whether at least one digit is required
+    pub require_digit: bool,
+}
+
+impl Default for EmailPasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: false,
+        }
+    }
+}
+
+impl EmailPasswordPolicy {
+    /// Checks the password against the policy
+    /// 
+    /// Returns [AuthError::ValidationErrors] with the `password` field on failure
+    pub fn validate(&self, password: &str) -> AuthResult<()> {
+        match self.password_error(password) {
+            Some(message) => Err(AuthError::ValidationErrors(HashMap::from([("password".into(), message)]))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the first rule the password breaks (if any)
+    fn password_error(&self, password: &str) -> Option<String> {
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            return Some(format!("The password must be at least {} characters long!", self.min_length));
+        }
+
+        if length > self.max_length {
+            return Some(format!("The password must be at most {} characters long!", self.max_length));
+        }
+
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Some("The password must contain an uppercase letter!".into());
+        }
+
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            return Some("The password must contain a lowercase letter!".into());
+        }
+
+        if self.require_digit && !password.chars().any(|char| char.is_ascii_digit()) {
+            return Some("The password must contain a digit!".into());
+        }
+
+        None
+    }
 }
 
 impl EmailPasswordMethod {
-    /// The auth method's constructor
-    pub fn new(email: String, password: String) -> Self {
-        Self { email, password }
+    /// The auth method's constructor, used to authenticate
+    /// 
+    /// The password isn't checked against any [EmailPasswordPolicy], so users whose password
+    /// predates a stricter policy can still log in.
+    /// 
+    /// # Note:
+    /// Credentials created this way (or deserialized) can't be registered, 
+    /// use [validated](Self::validated) or [validate](Self::validate) for that
+    pub fn new(email: Email, password: Password) -> Self {
+        Self { email, password, created_by_admin: false, policy_checked: false }
+    }
+
+    /// Checks the email's format and the password against the [EmailPasswordPolicy],
+    /// which allows the credential to be registered
+    /// 
+    /// Returns [AuthError::ValidationErrors] with the `email` and/or `password` fields on failure.
+    /// This is meant for credentials that were deserialized from a request,
+    /// otherwise use [validated](Self::validated).
+    pub fn validate(mut self, policy: &EmailPasswordPolicy) -> AuthResult<Self> {
+        let mut errors = HashMap::new();
+
+        if !self.email.is_valid() {
+            errors.insert("email".into(), "The email is invalid!".into());
+        }
+
+        if let Some(message) = policy.password_error(self.password.expose_secret()) {
+            errors.insert("password".into(), message);
+        }
+
+        if !errors.is_empty() {
+            return Err(AuthError::ValidationErrors(errors));
+        }
+
+        self.policy_checked = true;
+        Ok(self)
+    }
+
+    /// Makes sure the password went through an [EmailPasswordPolicy] before registering the credential
+    fn check_policy_applied(&self) -> AuthResult<()> {
+        if self.policy_checked {
+            Ok(())
+        } else {
+            Err(AuthError::Invalid("The password must be validated against a policy before registering!".into()))
+        }
+    }
+
+    /// Marks the credential as provisioned by an admin, which requires the [User] to change their password
+    /// 
+    /// # Note:
+    /// This can't be set through deserialization, only by the code creating the credential
+    pub fn provisioned_by_admin(mut self) -> Self {
+        self.created_by_admin = true;
+        self
+    }
+
+    /// Fetches the stored credential and checks the password against it
+    /// 
+    /// If the credential doesn't exist the password is verified against a dummy hash,
+    /// so both failure paths return the same error after the same amount of time.
+    async fn constant_time_authenticate(&self, db: &Surreal<Client>) -> AuthResult<DbEmailPasswordMethod> {
+        let credential = DbEmailPasswordMethod::find(db, &self.email)
+            .await?;
+
+        // Catches corrupted hashes before attempting verification
+        if let Some(credential) = &credential {
+            credential.validate_hash()?;
+        }
+
+        let hash = credential
+            .as_ref()
+            .map_or(dummy_hash(), |credential| credential.data.as_str());
+
+        // Checks if the password and the hash match
+        let verified = argon2()
+            .verify_password(self.password.expose_secret().as_bytes(), &PasswordHash::new(hash)?)
+            .is_ok();
+
+        match credential {
+            Some(credential) if verified => Ok(credential),
+            _ => Err(AuthError::CredentialNotFound("The email or password is incorrect!".into())),
+        }
+    }
+
+    /// Replaces the credential's password, after checking the current one
+    /// 
+    /// The new password is checked against the [EmailPasswordPolicy]. This is also how
+    /// admin-provisioned credentials stop requiring a password change.
+    /// 
+    /// # Example
+    /// ```ignore
+    /// EmailPasswordMethod::new(Email::new("email@example.com")?, current_password)
+    ///     .change_password(&db, new_password, &EmailPasswordPolicy::default())
+    ///     .await?;
+    /// ```
+    #[tracing::instrument(skip_all, fields(email = %self.email), err)]
+    pub async fn change_password(&self, db: &Surreal<Client>, password: Password, policy: &EmailPasswordPolicy) -> AuthResult<()> {
+        policy.validate(password.expose_secret())?;
+
+        self.constant_time_authenticate(db)
+            .await?
+            .update_password(db, &password)
+            .await
+    }
+
+    /// Creates the auth method after checking the password against the [EmailPasswordPolicy]
+    /// 
+    /// # Example
+    /// ```ignore
+    /// let credential = EmailPasswordMethod::validated(
+    ///         "email@example.com".into(),
+    ///         "Sup3r_S3cure_P4ssword".into(),
+    ///         &EmailPasswordPolicy::default()
+    ///     )?;
+    /// ```
+    pub fn validated(email: String, password: String, policy: &EmailPasswordPolicy) -> AuthResult<Self> {
+        Self::new(Email::normalized(&email), Password::new(password))
+            .validate(policy)
     }
 }
 
@@ -62,31 +374,37 @@ impl AuthMethod for EmailPasswordMethod {
 
     fn into_db(&self) -> AuthResult<Box<dyn DbAuthMethod>> {
         Ok(Box::new(DbEmailPasswordMethod {
-            id: Thing::from((
-                "credential".to_string(),
-                Id::Array(vec![self.r#type().to_string(), self.email.clone()].into()),
-            )),
-            data: Argon2::default()
-                .hash_password(self.password.as_bytes(), &SaltString::generate(&mut OsRng))?
+            id: DbEmailPasswordMethod::id_for(&self.email),
+            data: argon2()
+                .hash_password(self.password.expose_secret().as_bytes(), &SaltString::generate(&mut OsRng))?
                 .to_string(),
             associated_user: None,
+            created_by_admin: self.created_by_admin,
         }))
     }
 
-    async fn authenticate(&self, db: &Surreal<Client>, mfa: Option<MfaCode>) -> AuthResult<(User, AuthSessionId)> {
-        
+    async fn check_registration(&self, db: &Surreal<Client>) -> AuthResult<()> {
+        self.check_policy_applied()?;
+
+        // Catches accounts registered with a different casing before emails were normalized
+        if DbEmailPasswordMethod::exists_ignoring_case(db, &self.email).await? {
+            return Err(AuthError::CredentialDuplicate("This authentication method is already linked to another user!".into()));
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(email = tracing::field::Empty), err)]
+    async fn authenticate(&self, db: &Surreal<Client>) -> AuthResult<AuthStep> {
+        tracing::Span::current().record("email", self.email.as_ref());
+
         // Fetches the credential with the identifier (if it exists)
-        let credential: DbEmailPasswordMethod = db
-            .query("SELECT * FROM $credential_id->authenticates;")
-            .bind(("credential_id", self.into_db()?.id()))
-            .await?
-            .take::<Option<DbEmailPasswordMethod>>(0)?
-            .ok_or(AuthError::CredentialNotFound("1 The credential is incorrect or could not be found!".into()))?;
+        let credential = self
+            .constant_time_authenticate(db)
+            .await?;
 
-        // Checks if the password and the hash match
-        Argon2::default()
-            .verify_password(self.password.as_ref(), &PasswordHash::new(&credential.data)?)
-            .map_err(|_| AuthError::CredentialNotFound("2 The credential is incorrect or could not be found!".into()))?;
+        // Admin-provisioned passwords have to be replaced by the user
+        let requires_password_change = credential.requires_password_change();
 
         // Fetches the user associated with the user id
         let mut user: User = db
@@ -102,42 +420,24 @@ impl AuthMethod for EmailPasswordMethod {
             return Err(AuthError::UserDisabled(reason));
         }
 
-        // Checks the MFA code
-        match mfa {
-            Some(code) => {
-
-                let mfa_credentials = user
-                    .get_mfa_credentials(db, Some(code.method))
-                    .await?;
-
-                let mfa_credential = mfa_credentials.get(0);
+        // Checks if MFA is required
+        let methods = user.get_mfa_methods(db).await?;
 
-                if let Some(mfa_credential) = mfa_credential {
-
-                    // Checks if the code is invalid
-                    mfa_credential.verify(&user, db, code.data)
-                        .await?;
-                } else {
-
-                    // The MFA method isn't supported
-                    return Err(AuthError::CredentialNotFound("The MFA credential is incorrect!".into()))
-                }
-            },
-            None => {
+        if !methods.is_empty() {
 
-                // Checks if MFA is required
-                if user.get_mfa_methods(db).await?.len() > 0 {
+            // Creates the pending auth session
+            let session = user 
+                .create_auth_session(db, AuthSessionState::PendingMfa, None)
+                .await?;
 
-                    // Creates the auth session
-                    let session = user 
-                        .create_auth_session(db, AuthSessionState::PendingMfa, None)
-                        .await?;
-
-                    return Err(AuthError::MfaRequired(session));
-                }
-            }
+            return Ok(AuthStep::RequiresMfa { session, methods, requires_password_change });
         }
 
+        // Creates the auth session
+        let session = user
+            .create_auth_session(db, AuthSessionState::Authenticated, None)
+            .await?;
+
         // Updates the last access timestamp
         user.metadata.last_access = jsonwebtoken::get_current_timestamp();
         user
@@ -145,14 +445,221 @@ impl AuthMethod for EmailPasswordMethod {
             .await
             .map_err(|_| AuthError::UpdateFailed("Failed to update last access!".into()))?;
 
-        // Creates the auth session
-        let session = user
-            .create_auth_session(db, AuthSessionState::Authenticated, None)
-            .await?;
-
-        Ok((
+        Ok(AuthStep::Complete {
             user,
-            session
-        ))
+            session,
+            requires_password_change,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::AuthError, user::{email::Email, password::Password}};
+    use super::{dummy_hash, DbEmailPasswordMethod, EmailPasswordMethod, EmailPasswordPolicy};
+    use crate::user::credential::{argon2, AuthMethod};
+    use argon2::{password_hash::{rand_core::OsRng, SaltString}, PasswordHash, PasswordHasher, PasswordVerifier};
+    use surrealdb::{engine::remote::ws::Client, sql::Thing, Surreal};
+
+    #[test]
+    fn email_password_validate_hash() {
+        let credential = DbEmailPasswordMethod {
+            id: Thing::from(("credential".to_string(), "test".to_string())),
+            data: argon2()
+                .hash_password(b"Sup3r_S3cure_P4ssword", &SaltString::generate(&mut OsRng))
+                .unwrap()
+                .to_string(),
+            associated_user: None,
+            created_by_admin: false,
+        };
+
+        assert!(credential.validate_hash().is_ok())
+    }
+
+    #[test]
+    fn email_password_validate_hash_malformed() {
+        let credential = DbEmailPasswordMethod {
+            id: Thing::from(("credential".to_string(), "test".to_string())),
+            data: "not-a-hash".into(),
+            associated_user: None,
+            created_by_admin: false,
+        };
+
+        // The credential id contains the email, so it must not leak into the message
+        let error = credential.validate_hash().unwrap_err();
+        assert!(!error.to_string().contains("test"))
+    }
+
+    #[test]
+    fn email_password_hash_algorithm() {
+        let credential = EmailPasswordMethod::new(Email::new("email@example.com").unwrap(), Password::new("Sup3r_S3cure_P4ssword".into()))
+            .into_db()
+            .unwrap();
+        let credential = serde_json::to_value(credential).unwrap();
+
+        assert!(credential["data"].as_str().unwrap().starts_with("$argon2id$v=19$"))
+    }
+
+    #[test]
+    fn email_password_policy_default() {
+        let policy = EmailPasswordPolicy::default();
+
+        assert!(policy.validate("Sup3r_S3cure_P4ssword").is_ok());
+        assert!(policy.validate("short").is_err())
+    }
+
+    #[test]
+    fn email_password_policy_requirements() {
+        let policy = EmailPasswordPolicy {
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            ..Default::default()
+        };
+
+        assert!(policy.validate("Sup3r_S3cure_P4ssword").is_ok());
+        assert!(policy.validate("sup3r_s3cure_p4ssword").is_err());
+        assert!(policy.validate("SUP3R_S3CURE_P4SSWORD").is_err());
+        assert!(policy.validate("Super_Secure_Password").is_err())
+    }
+
+    #[test]
+    fn email_password_validated() {
+        let policy = EmailPasswordPolicy::default();
+
+        assert!(EmailPasswordMethod::validated("email@example.com".into(), "Sup3r_S3cure_P4ssword".into(), &policy).is_ok());
+        assert!(EmailPasswordMethod::validated("email@example.com".into(), "short".into(), &policy).is_err())
+    }
+
+    #[test]
+    fn email_password_validated_errors() {
+        let policy = EmailPasswordPolicy::default();
+
+        match EmailPasswordMethod::validated("email".into(), "short".into(), &policy) {
+            Err(AuthError::ValidationErrors(errors)) => {
+                assert!(errors.contains_key("email"));
+                assert!(errors.contains_key("password"))
+            },
+            _ => panic!("Expected validation errors!"),
+        }
+    }
+
+    #[test]
+    fn dummy_hash_params() {
+        let dummy = PasswordHash::new(dummy_hash()).unwrap();
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = argon2()
+            .hash_password(b"Sup3r_S3cure_P4ssword", &salt)
+            .unwrap();
+
+        // Same algorithm and cost, so verifying against it takes as long as a real hash
+        assert_eq!(dummy.algorithm, hash.algorithm);
+        assert_eq!(dummy.version, hash.version);
+        assert_eq!(dummy.params, hash.params);
+        assert!(argon2().verify_password(b"dummy_password", &dummy).is_ok())
+    }
+
+    #[test]
+    fn email_password_deserialize() {
+        let credential = serde_json::from_str::<Box<dyn AuthMethod>>(
+            r#"{ "type": "EmailPasswordMethod", "email": "email@example.com", "password": "Sup3r_S3cure_P4ssword" }"#
+        );
+
+        assert!(credential.is_ok())
+    }
+
+    #[test]
+    fn email_password_deserialize_unknown_fields() {
+        let credential = serde_json::from_str::<Box<dyn AuthMethod>>(
+            r#"{ "type": "EmailPasswordMethod", "email": "email@example.com", "password": "Sup3r_S3cure_P4ssword", "verified": true }"#
+        );
+
+        assert!(credential.is_err())
+    }
+
+    #[test]
+    fn db_email_password_debug_redacted() {
+        let credential = DbEmailPasswordMethod {
+            id: Thing::from(("credential", "email@example.com")),
+            data: "$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$aGFzaGhhc2g".into(),
+            associated_user: None,
+            created_by_admin: false,
+        };
+
+        assert!(!format!("{credential:?}").contains("argon2id"))
+    }
+
+    #[test]
+    fn email_password_provisioned_by_admin() {
+        let credential = EmailPasswordMethod::new(Email::new("email@example.com").unwrap(), Password::new("Sup3r_S3cure_P4ssword".into()))
+            .provisioned_by_admin()
+            .into_db()
+            .unwrap();
+
+        assert!(credential.created_by_admin());
+        assert!(credential.requires_password_change())
+    }
+
+    #[test]
+    fn email_password_registration_requires_policy() {
+        let email = Email::new("email@example.com").unwrap();
+        let credential = EmailPasswordMethod::new(email, Password::new("weak".into()));
+
+        assert!(credential.check_policy_applied().is_err());
+        assert!(credential.clone().validate(&EmailPasswordPolicy::default()).is_err());
+
+        let credential = EmailPasswordMethod::validated("email@example.com".into(), "Sup3r_S3cure_P4ssword".into(), &EmailPasswordPolicy::default())
+            .unwrap();
+
+        assert!(credential.check_policy_applied().is_ok())
+    }
+
+    #[test]
+    fn email_password_deserialized_requires_policy() {
+        let credential: EmailPasswordMethod = serde_json::from_str(
+            r#"{ "email": "email@example.com", "password": "Sup3r_S3cure_P4ssword" }"#
+        ).unwrap();
+
+        assert!(credential.check_policy_applied().is_err());
+        assert!(credential.validate(&EmailPasswordPolicy::default()).unwrap().check_policy_applied().is_ok())
+    }
+
+    #[test]
+    fn email_password_deserialize_invalid_email() {
+        let credential = serde_json::from_str::<EmailPasswordMethod>(
+            r#"{ "email": "Email@", "password": "Sup3r_S3cure_P4ssword" }"#
+        );
+
+        assert!(credential.is_err())
+    }
+
+    #[test]
+    fn email_password_validate_invalid_email() {
+        let credential = EmailPasswordMethod::new(Email::normalized("Email@"), Password::new("Sup3r_S3cure_P4ssword".into()));
+
+        match credential.validate(&EmailPasswordPolicy::default()) {
+            Err(AuthError::ValidationErrors(errors)) => assert!(errors.contains_key("email")),
+            result => panic!("expected validation errors, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn email_password_serialize_round_trip() {
+        let credential = EmailPasswordMethod::new(Email::new("email@example.com").unwrap(), Password::new("Sup3r_S3cure_P4ssword".into()));
+        let serialized = serde_json::to_string(&credential).unwrap();
+
+        assert!(!serialized.contains("Sup3r_S3cure_P4ssword"));
+        assert!(serde_json::from_str::<EmailPasswordMethod>(&serialized).is_err())
+    }
+
+    // The policy is checked before the DB is used, so the client is never connected
+    #[tokio::test]
+    async fn email_password_change_password_requires_policy() {
+        let credential = EmailPasswordMethod::new(Email::new("email@example.com").unwrap(), Password::new("Sup3r_S3cure_P4ssword".into()));
+        let result = credential
+            .change_password(&Surreal::<Client>::init(), Password::new("short".into()), &EmailPasswordPolicy::default())
+            .await;
+
+        assert!(matches!(result, Err(AuthError::ValidationErrors(_))))
     }
 }
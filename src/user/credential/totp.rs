@@ -1,29 +1,41 @@
+use std::fmt;
+
 use rand::{distributions::Uniform, Rng};
 use serde::{Serialize, Deserialize};
-use surrealdb::{engine::remote::ws::Client, sql::{Id, Thing}, Surreal};
-use totp_rs::TOTP;
+use surrealdb::{engine::remote::ws::Client, sql::{Id, Thing, Value}, Surreal};
+use totp_rs::{Secret, TOTP};
 use uuid::Uuid;
 use crate::{prelude::*, session::{AuthSessionId, AuthSessionState}};
-use super::{MfaMethod, MfaMethodType};
+use super::{AuthMethodType, MfaMethod, MfaMethodType};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TotpMethod {
     #[serde(rename(deserialize = "in"))]
     id: Thing,
     secret: Vec<u8>,
 }
 
+impl fmt::Debug for TotpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TotpMethod")
+            .field("id", &self.id)
+            .field("secret", &"[REDACTED]")
+            .finish()
+    }
+}
+
 impl TotpMethod {
     const CODE_LENGTH: usize = 6;
     const CODE_SKEW: u8 = 1;
     const CODE_DURATION: u64 = 30;
+    const SECRET_LENGTH: usize = 128;
 
     pub fn new (user_id: Uuid) -> Self {
 
         // Generates the secret
         let secret = rand::thread_rng()
-            .sample_iter(Uniform::new(u8::MIN, u8::MAX))
-            .take(128)
+            .sample_iter(Uniform::new_inclusive(u8::MIN, u8::MAX))
+            .take(Self::SECRET_LENGTH)
             .collect();
 
         Self { 
@@ -35,34 +47,80 @@ impl TotpMethod {
         }
     }
 
-    pub fn get_qr_code(&self) -> AuthResult<String> {
-        let totp = TOTP::new(
+    /// Gets the issuer shown in authenticator apps from the `TOTP_ISSUER` env variable
+    fn get_issuer() -> String {
+        std::env::var("TOTP_ISSUER").unwrap_or_else(|_| String::from("auth.server.com"))
+    }
+
+    /// Gets the UUID of the [User] that owns the method from its id
+    fn get_user_id(&self) -> AuthResult<Uuid> {
+        match &self.id.id {
+            Id::Array(array) => match array.0.get(1) {
+                Some(Value::Strand(user_id)) => Uuid::parse_str(user_id.as_str())
+                    .map_err(|_| AuthError::Unknown("The TOTP method id is malformed!".into())),
+                _ => Err(AuthError::Unknown("The TOTP method id is malformed!".into())),
+            },
+            _ => Err(AuthError::Unknown("The TOTP method id is malformed!".into())),
+        }
+    }
+
+    /// Gets the account name shown in authenticator apps
+    /// 
+    /// This is the [User]'s email if they have an email/password credential,
+    /// otherwise it falls back to their UUID.
+    async fn get_account_name(&self, db: &Surreal<Client>) -> AuthResult<String> {
+        let user = User::get_by_uuid_any(db, &self.get_user_id()?)
+            .await?;
+
+        let email = user
+            .get_auth_credentials(db, Some(AuthMethodType::EmailPassword))
+            .await?
+            .first()
+            .and_then(|credential| match &credential.id().id {
+                Id::Array(array) => match array.0.get(1) {
+                    Some(Value::Strand(email)) => Some(email.to_string()),
+                    _ => None,
+                },
+                _ => None,
+            });
+
+        Ok(email.unwrap_or(user.id.to_string()))
+    }
+
+    fn get_totp(&self, account_name: String) -> AuthResult<TOTP> {
+        Ok(TOTP::new(
             totp_rs::Algorithm::SHA1,
             Self::CODE_LENGTH,
             Self::CODE_SKEW, 
             Self::CODE_DURATION,
-            self.secret.clone(),
-            Some(String::from("Underslight")),
-            String::from("test") 
-        )?;
+            self.secret_bytes().to_vec(),
+            Some(Self::get_issuer()),
+            account_name
+        )?)
+    }
 
-        totp
+    /// Generates the base64 QR code that can be scanned by authenticator apps
+    #[tracing::instrument(skip_all, err)]
+    pub async fn get_qr_code(&self, db: &Surreal<Client>) -> AuthResult<String> {
+        self.get_totp(self.get_account_name(db).await?)?
             .get_qr_base64()
-            .map_err(|err| AuthError::Unknown(err))
+            .map_err(AuthError::Unknown)
     }
 
-    pub fn get_secret(&self) -> AuthResult<String> {
-        let totp = TOTP::new(
-            totp_rs::Algorithm::SHA1,
-            Self::CODE_LENGTH,
-            Self::CODE_SKEW, 
-            Self::CODE_DURATION,
-            self.secret.clone(),
-            Some(String::from("Underslight")),
-            String::from("test")
-        )?;
+    /// Returns the base32 encoded secret, which can be displayed in setup UIs
+    /// for manual entry
+    pub fn secret_base32(&self) -> String {
+        Secret::Raw(self.secret.clone())
+            .to_encoded()
+            .to_string()
+    }
 
-        Ok(totp.get_secret_base32())
+    /// Returns the raw secret bytes
+    /// 
+    /// # Note:
+    /// These should never leave the crate, use [secret_base32](Self::secret_base32) instead
+    pub(crate) fn secret_bytes(&self) -> &[u8] {
+        &self.secret
     }
 }
 
@@ -77,17 +135,10 @@ impl MfaMethod for TotpMethod {
         MfaMethodType::Totp
     }   
 
+    #[tracing::instrument(skip_all, fields(user = %user.id), err)]
     async fn verify(&self, user: &User, db: &Surreal<Client>, token: String) -> AuthResult<AuthSessionId> {
         
-        let totp = TOTP::new( 
-            totp_rs::Algorithm::SHA1,
-            Self::CODE_LENGTH,
-            Self::CODE_SKEW, 
-            Self::CODE_DURATION,
-            self.secret.clone(),
-            Some(String::from("Underslight")),
-            String::from("test")
-        )?;
+        let totp = self.get_totp(user.id.to_string())?;
 
         let valid = totp.check(
             token.as_str(), 
@@ -103,13 +154,23 @@ impl MfaMethod for TotpMethod {
 
             return Ok(session);
         } else {
-
-            // Creates the pending auth session
-            let session = user 
-                .create_auth_session(db, AuthSessionState::PendingMfa, None)
-                .await?;
-
-            return Err(AuthError::MfaRequired(session));
+            return Err(AuthError::MfaIncorrect);
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::TotpMethod;
+    use totp_rs::Secret;
+    use uuid::Uuid;
+
+    #[test]
+    fn totp_secret_base32() {
+        let totp = TotpMethod::new(Uuid::new_v4());
+        let secret = Secret::Encoded(totp.secret_base32())
+            .to_bytes()
+            .unwrap();
+
+        assert_eq!(secret, totp.secret_bytes())
+    }
+}
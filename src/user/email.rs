@@ -0,0 +1,171 @@
+use std::{fmt, hash::{Hash, Hasher}, ops::Deref};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::prelude::*;
+
+/// A normalized email address
+///
+/// The address is trimmed and lowercased on construction, so two [Emails](Email)
+/// that only differ in case point to the same credential. The address as it was
+/// given is kept aside, since credentials registered before emails were normalized
+/// are stored under it.
+///
+/// # Example
+/// ```ignore
+/// let email = Email::new(" Someone@Example.com ")?;
+///
+/// assert_eq!(email.as_ref(), "someone@example.com");
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Email {
+    normalized: String,
+    raw: String,
+}
+
+impl Email {
+    /// Validates and normalizes the given email
+    ///
+    /// Only the basic shape is checked (a single `@`, a non-empty local part and a dotted domain):
+    /// the address itself can only be verified by sending an email to it.
+    pub fn new(raw: &str) -> AuthResult<Self> {
+        let email = Self::normalized(raw);
+
+        if email.is_valid() {
+            Ok(email)
+        } else {
+            Err(AuthError::Invalid("The email is invalid!".into()))
+        }
+    }
+
+    /// Normalizes the given email without validating it
+    /// 
+    /// This is meant for looking up existing credentials (e.g. to authenticate), which may
+    /// have been registered before the format was validated.
+    pub fn normalized(raw: &str) -> Self {
+        Self {
+            normalized: raw.trim().to_lowercase(),
+            raw: raw.to_string(),
+        }
+    }
+
+    /// Whether the email has a valid format
+    pub fn is_valid(&self) -> bool {
+        match self.normalized.split_once('@') {
+            Some((local, domain)) => !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+                && !self.normalized.chars().any(char::is_whitespace),
+            None => false,
+        }
+    }
+
+    /// Returns the email exactly as it was given, before normalization
+    pub(crate) fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Deserializes an email without validating it, only normalizing it
+    /// 
+    /// Unlike [Email]'s [Deserialize] (which is meant for client input), this is for
+    /// fields read back from the DB, which may have been stored before emails were validated.
+    pub(crate) fn deserialize_normalized<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::normalized(&String::deserialize(deserializer)?))
+    }
+}
+
+impl PartialEq for Email {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl Eq for Email {}
+
+impl Hash for Email {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = AuthError;
+
+    fn try_from(value: String) -> AuthResult<Self> {
+        Self::new(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(value: Email) -> Self {
+        value.normalized
+    }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str {
+        &self.normalized
+    }
+}
+
+impl Deref for Email {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.normalized
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.normalized)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Email;
+
+    #[test]
+    fn email_normalized() {
+        let email = Email::new(" Someone@Example.COM ").unwrap();
+
+        assert_eq!(email.as_ref(), "someone@example.com")
+    }
+
+    #[test]
+    fn email_invalid() {
+        for raw in ["", "someone", "@example.com", "someone@", "someone@example", "some@one@example.com", "some one@example.com", "someone@example..com"] {
+            assert!(Email::new(raw).is_err(), "{raw} should be invalid");
+        }
+    }
+
+    #[test]
+    fn email_deserialize_normalizes() {
+        let email = serde_json::from_str::<Email>("\"Someone@Example.com\"").unwrap();
+
+        assert_eq!(email.as_ref(), "someone@example.com");
+        assert_eq!(email.raw(), "Someone@Example.com")
+    }
+
+    #[test]
+    fn email_deserialize_invalid() {
+        assert!(serde_json::from_str::<Email>("\"someone\"").is_err())
+    }
+
+    #[test]
+    fn email_deserialize_normalized_skips_validation() {
+        let mut deserializer = serde_json::Deserializer::from_str("\"Someone\"");
+        let email = Email::deserialize_normalized(&mut deserializer).unwrap();
+
+        assert_eq!(email.as_ref(), "someone");
+        assert!(!email.is_valid())
+    }
+
+    #[test]
+    fn email_eq_ignores_case() {
+        assert_eq!(Email::new("Someone@Example.com").unwrap(), Email::new("someone@example.com").unwrap())
+    }
+}
@@ -3,6 +3,7 @@ use crate::prelude::*;
 
 use serde::Deserialize;
 use serde::Serialize;
+use std::net::IpAddr;
 
 /// Contains the auth metadata for a [User]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -15,7 +16,14 @@ pub struct UserMetadata {
     pub last_access: u64,
     /// The last password reset timestamp
     pub last_reset: u64,
-
+    /// The account creation timestamp
+    /// 
+    /// This is `None` for accounts registered before the creation time was tracked
+    #[serde(default)]
+    pub created: Option<u64>,
+    /// The IP address the account was registered from (if known)
+    #[serde(default)]
+    pub registration_ip: Option<IpAddr>,
 }
 
 impl Default for UserMetadata {
@@ -38,6 +46,8 @@ impl Buildable for UserMetadata {
 ///     .verified(true)
 ///     .last_access(timestamp)
 ///     .last_reset(timestamp)
+///     .created(timestamp)
+///     .registration_ip("127.0.0.1".parse().unwrap())
 ///     .build_safe();
 /// ```
 #[derive(Clone, Debug)]
@@ -50,6 +60,10 @@ pub struct UserMetadataBuilder {
     pub last_access: Option<u64>,
     /// The last password reset timestamp
     pub last_reset: Option<u64>,
+    /// The account creation timestamp
+    pub created: Option<u64>,
+    /// The IP address the account was registered from
+    pub registration_ip: Option<IpAddr>,
 }
 
 impl Default for UserMetadataBuilder {
@@ -61,6 +75,8 @@ impl Default for UserMetadataBuilder {
             verified: Some(false),
             last_access: Some(timestamp),
             last_reset: Some(timestamp),
+            created: Some(timestamp),
+            registration_ip: None,
         }
     }
 }
@@ -79,10 +95,7 @@ impl Builder for UserMetadataBuilder {
     fn build_safe(&self) -> Self::Buildable {
         Self::Buildable {
             disabled: self.disabled.clone(),
-            verified: match self.verified {
-                Some(verified) => verified,
-                None => false,
-            },
+            verified: self.verified.unwrap_or_default(),
             last_access: match self.last_access {
                 Some(last_access) => last_access,
                 None => jsonwebtoken::get_current_timestamp(),
@@ -91,6 +104,11 @@ impl Builder for UserMetadataBuilder {
                 Some(last_reset) => last_reset,
                 None => jsonwebtoken::get_current_timestamp(),
             },
+            created: Some(match self.created {
+                Some(created) => created,
+                None => jsonwebtoken::get_current_timestamp(),
+            }),
+            registration_ip: self.registration_ip,
         }
     }
 }
@@ -148,10 +166,37 @@ impl UserMetadataBuilder {
         self.last_reset = Some(last_reset);
         self
     }
+
+    /// Sets the account creation timestamp
+    /// 
+    /// # Example
+    /// ```ignore
+    /// let metadata = UserMetadata::builder()
+    ///     .created(get_current_timestamp())
+    ///     .build_safe();
+    /// ```
+    pub fn created(&mut self, created: u64) -> &mut Self {
+        self.created = Some(created);
+        self
+    }
+
+    /// Sets the IP address the account was registered from
+    /// 
+    /// # Example
+    /// ```ignore
+    /// let metadata = UserMetadata::builder()
+    ///     .registration_ip(client_ip)
+    ///     .build_safe();
+    /// ```
+    pub fn registration_ip(&mut self, registration_ip: IpAddr) -> &mut Self {
+        self.registration_ip = Some(registration_ip);
+        self
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::net::IpAddr;
     use super::UserMetadata;
     use crate::builder::*;
 
@@ -173,7 +218,7 @@ mod test {
             .disabled(vec!["due to inactivity (24 months)".into()])
             .build_safe();
 
-        assert_eq!(metadata.disabled, Some(vec![String::from("due to inactivity (24 months")]))
+        assert_eq!(metadata.disabled, Some(vec![String::from("due to inactivity (24 months)")]))
     }
 
     #[test]
@@ -182,7 +227,7 @@ mod test {
             .verified(true)
             .build_safe();
 
-        assert_eq!(metadata.verified, true)
+        assert!(metadata.verified)
     }
 
     #[test]
@@ -202,4 +247,32 @@ mod test {
 
         assert_eq!(metadata.last_reset, 0)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn metadata_builder_created() {
+        let metadata = UserMetadata::builder()
+            .created(0)
+            .build_safe();
+
+        assert_eq!(metadata.created, Some(0))
+    }
+
+    #[test]
+    fn metadata_builder_registration_ip() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let metadata = UserMetadata::builder()
+            .registration_ip(ip)
+            .build_safe();
+
+        assert_eq!(metadata.registration_ip, Some(ip))
+    }
+
+    #[test]
+    fn metadata_deserialize_without_created() {
+        let metadata: UserMetadata = serde_json::from_str(
+            r#"{ "disabled": null, "verified": false, "last_access": 0, "last_reset": 0 }"#
+        ).unwrap();
+
+        assert_eq!(metadata.created, None)
+    }
+}
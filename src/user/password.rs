@@ -0,0 +1,60 @@
+use std::fmt;
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+/// A plaintext password
+///
+/// The value is zeroed when it's dropped and it's never printed: [Debug] outputs `[REDACTED]`.
+/// It can't be serialized either, since a placeholder would be deserialized back as the password.
+///
+/// # Example
+/// ```ignore
+/// let password = Password::new("Sup3r_S3cure_P4ssword".into());
+///
+/// assert_eq!(format!("{password:?}"), "[REDACTED]");
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Password(SecretString);
+
+impl Password {
+    /// Wraps the given password
+    pub fn new(raw: String) -> Self {
+        Self(raw.into())
+    }
+
+    /// Returns the plaintext password
+    ///
+    /// # Note:
+    /// This should only be used for hashing and verification
+    pub fn expose_secret(&self) -> &str {
+        self.0.expose_secret()
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Password;
+
+    #[test]
+    fn password_redacted() {
+        let password = Password::new("Sup3r_S3cure_P4ssword".into());
+
+        assert_eq!(format!("{password:?}"), "[REDACTED]");
+        assert_eq!(password.expose_secret(), "Sup3r_S3cure_P4ssword")
+    }
+
+    #[test]
+    fn password_deserialize() {
+        let password: Password = serde_json::from_str("\"Sup3r_S3cure_P4ssword\"").unwrap();
+
+        assert_eq!(password.expose_secret(), "Sup3r_S3cure_P4ssword")
+    }
+}
@@ -145,7 +145,7 @@ impl IdToken {
         let refresh_claims = self.refresh.verify(None)?;
 
         if access_claims.sub != refresh_claims.sub {
-            return Err(AuthError::TokenInvalid);
+            return Err(AuthError::TokenInvalid(None));
         }
 
         let current_timestamp = get_current_timestamp();
@@ -161,7 +161,7 @@ impl IdToken {
         }
 
         // Refreshes the token
-        let id_token = User::get_by_uuid(db, &access_claims.sub)
+        let id_token = User::get_by_uuid_active(db, &access_claims.sub)
             .await?
             .get_id_token()?;
 